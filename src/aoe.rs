@@ -0,0 +1,447 @@
+//! AoEs, and who they hit.
+//!
+//! An AoE is a [`Shape`] marked with [`Aoe`]. Every [`Player`](crate::player::Player) is tested
+//! against every AoE, and the AoEs that hit them are kept in their [`HitBy`]. Players who are hit
+//! are tinted so that they stand out.
+//!
+//! An AoE can also have an [`AoeRule`] saying who should be hit by it, such as a stack that needs
+//! enough players inside or a tankbuster that only tanks may take. Each rule is checked against
+//! the players the AoE hits, and the result is shown on a label at the AoE's origin.
+
+use std::collections::HashMap;
+
+use bevy::{
+    ecs::{component::ComponentId, world::DeferredWorld},
+    prelude::*,
+    transform::helper::TransformHelper,
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "egui")]
+use crate::label::{text_label, LABEL_FONT_SIZE};
+use crate::{
+    ecs::TransformHelperExt,
+    player::{job::Role, PlayerSprite},
+    shape::{Shape, ShapeAnchor},
+};
+
+/// The tint applied to players who are hit by an AoE.
+const HIT_TINT: Color = Color::srgb(1.0, 0.35, 0.35);
+/// The color of the label of a rule that passes.
+const PASSED_COLOR: Color = Color::srgb(0.4, 0.9, 0.4);
+/// The color of the label of a rule that fails.
+const FAILED_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+
+/// Marker component for shapes that are AoEs, which hit any player standing inside them.
+#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
+#[require(Shape(|| -> Shape { panic!("Aoe must have a Shape") }))]
+pub struct Aoe;
+
+/// The AoEs that hit a player, in no particular order.
+///
+/// A player is hit by an AoE if their center is inside it.
+#[derive(Component, Reflect, Default, Clone, Debug, PartialEq, Eq)]
+pub struct HitBy(pub Vec<Entity>);
+
+impl HitBy {
+    pub fn is_hit(&self) -> bool { !self.0.is_empty() }
+
+    /// [System] that tests everything with a [`HitBy`] against every AoE.
+    ///
+    /// Uses the latest [`Transform`]s, so that hits are updated while players and AoEs are
+    /// dragged.
+    pub fn update_hits(
+        mut target_q: Query<(Entity, &mut HitBy)>,
+        aoe_q: Query<(Entity, &Shape), With<Aoe>>,
+        helper: TransformHelper,
+    ) {
+        let aoes = aoe_q
+            .iter()
+            .filter_map(|(id, shape)| {
+                let transform = helper.compute_global_transform(id).ok()?;
+                Some((id, shape, transform.affine().inverse()))
+            })
+            .collect::<Vec<_>>();
+
+        for (id, mut hits) in &mut target_q {
+            let Ok(position) = helper.world_position(id) else {
+                continue;
+            };
+            let position = position.extend(0.0);
+            let mut new = aoes
+                .iter()
+                .filter(|(_, shape, to_local)| {
+                    shape.contains(to_local.transform_point3(position).truncate())
+                })
+                .map(|&(aoe, ..)| aoe)
+                .collect::<Vec<_>>();
+            new.sort();
+            hits.set_if_neq(HitBy(new));
+        }
+    }
+
+    /// [System] that tints the sprites of players who are hit.
+    ///
+    /// Only the color is changed; the alpha is left to [`AlphaScale`](crate::color::AlphaScale).
+    #[cfg(feature = "egui")]
+    pub fn update_tints(mut q: Query<(&HitBy, &mut Sprite), Changed<HitBy>>) {
+        for (hits, mut sprite) in &mut q {
+            let tint = if hits.is_hit() {
+                HIT_TINT
+            } else {
+                Color::WHITE
+            };
+            sprite.color = tint.with_alpha(sprite.color.alpha());
+        }
+    }
+}
+
+/// Who should be hit by an AoE.
+///
+/// Having a rule makes a shape an [`Aoe`].
+#[derive(Component, Reflect, Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+#[require(RuleCheck, Aoe)]
+#[component(on_add = Self::on_add)]
+#[component(on_remove = Self::on_remove)]
+pub enum AoeRule {
+    /// At least this many players must be inside.
+    Stack(usize),
+    /// Nobody may be inside except the player the AoE is anchored to.
+    Spread,
+    /// Exactly this many players must be inside, all of whom must be allowed to soak it.
+    Tower {
+        count: usize,
+        #[serde(default)]
+        soakers: Soakers,
+    },
+    /// Only tanks may be inside.
+    Tankbuster,
+}
+
+/// Which players may soak an [`AoeRule::Tower`].
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Reflect, Serialize, Deserialize)]
+pub enum Soakers {
+    #[default]
+    Any,
+    Tanks,
+    Healers,
+    Dps,
+}
+
+impl Soakers {
+    /// Returns true if a player with the given role may soak. Players without a known role may
+    /// only soak towers that anyone can.
+    pub fn allows(self, role: Option<Role>) -> bool {
+        match self {
+            Soakers::Any => true,
+            Soakers::Tanks => role.is_some_and(Role::is_tank),
+            Soakers::Healers => role.is_some_and(Role::is_healer),
+            Soakers::Dps => role.is_some_and(Role::is_dps),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Soakers::Any => "Any",
+            Soakers::Tanks => "Tanks",
+            Soakers::Healers => "Healers",
+            Soakers::Dps => "DPS",
+        }
+    }
+}
+
+/// The result of checking an [`AoeRule`].
+#[derive(Component, Reflect, Default, Clone, Debug, PartialEq, Eq)]
+pub struct RuleCheck {
+    pub passed: bool,
+    /// The number of players inside the AoE.
+    pub inside: usize,
+    /// The players inside the AoE who shouldn't be, in no particular order.
+    pub offenders: Vec<Entity>,
+}
+
+/// Marker component for the label showing the [`RuleCheck`] of an AoE.
+#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
+pub struct RuleLabel;
+
+impl AoeRule {
+    /// Checks the rule against the players inside an AoE, given as their entities and roles.
+    ///
+    /// `target` is the player the AoE is anchored to, if any.
+    pub fn check(self, inside: &[(Entity, Option<Role>)], target: Option<Entity>) -> RuleCheck {
+        let offenders = |allowed: &dyn Fn(Entity, Option<Role>) -> bool| {
+            inside
+                .iter()
+                .filter(|&&(id, role)| !allowed(id, role))
+                .map(|&(id, _)| id)
+                .collect_vec()
+        };
+        let (passed, offenders) = match self {
+            AoeRule::Stack(min) => (inside.len() >= min, vec![]),
+            AoeRule::Spread => {
+                let offenders = offenders(&|id, _| Some(id) == target);
+                (offenders.is_empty(), offenders)
+            }
+            AoeRule::Tower { count, soakers } => {
+                let offenders = offenders(&|_, role| soakers.allows(role));
+                (offenders.is_empty() && inside.len() == count, offenders)
+            }
+            AoeRule::Tankbuster => {
+                let offenders = offenders(&|_, role| role.is_some_and(Role::is_tank));
+                (offenders.is_empty(), offenders)
+            }
+        };
+        RuleCheck {
+            passed,
+            inside: inside.len(),
+            offenders,
+        }
+    }
+
+    /// Returns true if a player with the given role may step into the AoE without breaking its
+    /// rule, as long as it isn't already full.
+    pub fn welcomes(self, role: Option<Role>) -> bool {
+        match self {
+            AoeRule::Stack(_) => true,
+            AoeRule::Tower { soakers, .. } => soakers.allows(role),
+            AoeRule::Spread | AoeRule::Tankbuster => false,
+        }
+    }
+
+    /// Produces a short description of the rule, like "Stack (4+)".
+    pub fn label(self) -> String {
+        match self {
+            AoeRule::Stack(min) => format!("Stack ({min}+)"),
+            AoeRule::Spread => "Spread".to_owned(),
+            AoeRule::Tower {
+                count,
+                soakers: Soakers::Any,
+            } => format!("Tower ({count})"),
+            AoeRule::Tower { count, soakers } => format!("Tower ({count} {})", soakers.name()),
+            AoeRule::Tankbuster => "Tankbuster".to_owned(),
+        }
+    }
+
+    /// Adds the label.
+    fn on_add(mut world: DeferredWorld, id: Entity, _: ComponentId) {
+        #[cfg(feature = "egui")]
+        world.commands().entity(id).with_child((
+            Name::new("Rule Label"),
+            RuleLabel,
+            text_label(LABEL_FONT_SIZE, PASSED_COLOR, Vec2::ZERO),
+        ));
+    }
+
+    /// Removes the result and the label.
+    fn on_remove(mut world: DeferredWorld, id: Entity, _: ComponentId) {
+        world.commands().queue(move |world: &mut World| {
+            // The AoE may have been despawned along with its label, or given a new rule since.
+            let Ok(mut entity) = world.get_entity_mut(id) else {
+                return;
+            };
+            if entity.contains::<AoeRule>() {
+                return;
+            }
+            entity.remove::<RuleCheck>();
+            let children = entity
+                .get::<Children>()
+                .map_or_else(Vec::new, |children| children.to_vec());
+            for child in children {
+                if world.entity(child).contains::<RuleLabel>() {
+                    world.entity_mut(child).despawn_recursive();
+                }
+            }
+        });
+    }
+
+    /// [System] that checks the rule of every AoE against the players it hits.
+    pub fn check_rules(
+        mut aoe_q: Query<(Entity, &AoeRule, Option<&ShapeAnchor>, &mut RuleCheck)>,
+        player_q: Query<(Entity, &HitBy, Option<&PlayerSprite>)>,
+    ) {
+        let mut inside = HashMap::<Entity, Vec<(Entity, Option<Role>)>>::new();
+        for (id, hits, sprite) in &player_q {
+            for &aoe in &hits.0 {
+                let role = sprite.and_then(|sprite| sprite.role());
+                inside.entry(aoe).or_default().push((id, role));
+            }
+        }
+        for (id, rule, anchor, mut check) in &mut aoe_q {
+            let mut players = inside.remove(&id).unwrap_or_default();
+            players.sort();
+            let target = anchor.map(|anchor| anchor.target);
+            check.set_if_neq(rule.check(&players, target));
+        }
+    }
+
+    /// [System] that updates the labels of AoEs whose rules or results have changed, listing the
+    /// names of any offending players.
+    #[cfg(feature = "egui")]
+    #[allow(clippy::type_complexity)]
+    pub fn update_labels(
+        q: Query<(&AoeRule, &RuleCheck, &Children), Or<(Changed<AoeRule>, Changed<RuleCheck>)>>,
+        name_q: Query<&Name>,
+        mut label_q: Query<(&mut Text2d, &mut TextColor), With<RuleLabel>>,
+    ) {
+        for (rule, check, children) in &q {
+            let text = if check.passed {
+                format!("{}: OK", rule.label())
+            } else if check.offenders.is_empty() {
+                format!("{}: {} inside", rule.label(), check.inside)
+            } else {
+                let names = name_q.iter_many(&check.offenders).join(", ");
+                format!("{}: {names}", rule.label())
+            };
+            let mut labels = label_q.iter_many_mut(children);
+            while let Some((mut label, mut color)) = labels.fetch_next() {
+                label.0.clone_from(&text);
+                color.0 = if check.passed {
+                    PASSED_COLOR
+                } else {
+                    FAILED_COLOR
+                };
+            }
+        }
+    }
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct AoePlugin;
+
+impl Plugin for AoePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Aoe>()
+            .register_type::<HitBy>()
+            .register_type::<AoeRule>()
+            .register_type::<RuleCheck>()
+            .add_systems(
+                PostUpdate,
+                (
+                    HitBy::update_hits
+                        .after(ShapeAnchor::follow_targets)
+                        .before(TransformSystem::TransformPropagate),
+                    AoeRule::check_rules.after(HitBy::update_hits),
+                ),
+            );
+        #[cfg(feature = "egui")]
+        app.add_systems(
+            PostUpdate,
+            (
+                HitBy::update_tints.after(HitBy::update_hits),
+                AoeRule::update_labels.after(AoeRule::check_rules),
+            ),
+        );
+    }
+}
+
+pub fn plugin() -> AoePlugin { AoePlugin }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aoes_hit_players_inside_them() {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), TransformPlugin, plugin()));
+        let world = app.world_mut();
+        let inside = world
+            .spawn((HitBy::default(), Transform::from_xyz(1.0, 0.0, 0.0)))
+            .id();
+        let outside = world
+            .spawn((HitBy::default(), Transform::from_xyz(10.0, 0.0, 0.0)))
+            .id();
+        let circle = world
+            .spawn((Aoe, Shape::Circle(Circle::new(5.0)), Transform::default()))
+            .id();
+        // A 10 by 2 rectangle, turned to run north to south through the second player.
+        let line = world
+            .spawn((
+                Aoe,
+                Shape::Rectangle(Rectangle::new(10.0, 2.0)),
+                Transform::from_xyz(10.0, 0.0, 0.0)
+                    .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2)),
+            ))
+            .id();
+        app.update();
+
+        let hits = |app: &App, id| app.world().get::<HitBy>(id).unwrap().clone();
+        assert_eq!(hits(&app, inside), HitBy(vec![circle]));
+        assert_eq!(hits(&app, outside), HitBy(vec![line]));
+
+        app.world_mut()
+            .get_mut::<Transform>(inside)
+            .unwrap()
+            .translation
+            .y = 3.0;
+        app.world_mut().despawn(circle);
+        app.update();
+        assert!(!hits(&app, inside).is_hit());
+    }
+
+    #[test]
+    fn removing_a_rule_removes_its_result() {
+        let mut world = World::new();
+        let aoe = world
+            .spawn((
+                AoeRule::Stack(4),
+                Shape::Circle(Circle::new(5.0)),
+                Transform::default(),
+            ))
+            .id();
+        world.flush();
+        assert!(world.entity(aoe).contains::<Aoe>());
+        assert!(world.entity(aoe).contains::<RuleCheck>());
+
+        world.entity_mut(aoe).remove::<AoeRule>();
+        world.flush();
+        assert!(!world.entity(aoe).contains::<RuleCheck>());
+        assert!(world
+            .get::<Children>(aoe)
+            .is_none_or(|children| children.is_empty()));
+    }
+
+    #[test]
+    fn aoe_rules() {
+        let [mt, h1, m1, r1] = [1, 2, 3, 4].map(Entity::from_raw);
+        let party = [
+            (mt, Some(Role::Tank)),
+            (h1, Some(Role::PureHealer)),
+            (m1, Some(Role::Melee)),
+            (r1, Some(Role::Caster)),
+        ];
+
+        let stack = AoeRule::Stack(4);
+        assert!(stack.check(&party, None).passed);
+        let short = stack.check(&party[..3], None);
+        assert!(!short.passed);
+        assert_eq!((short.inside, short.offenders), (3, vec![]));
+
+        // Only the target of a spread may be inside it.
+        assert!(AoeRule::Spread.check(&party[2..3], Some(m1)).passed);
+        assert_eq!(
+            AoeRule::Spread.check(&party[1..3], Some(m1)).offenders,
+            vec![h1]
+        );
+
+        let tower = AoeRule::Tower {
+            count: 2,
+            soakers: Soakers::Dps,
+        };
+        assert!(tower.check(&party[2..], None).passed);
+        assert_eq!(tower.check(&party[1..3], None).offenders, vec![h1]);
+        assert!(!tower.check(&party[3..], None).passed);
+        assert_eq!(tower.label(), "Tower (2 DPS)");
+
+        assert!(AoeRule::Tankbuster.check(&party[..1], Some(mt)).passed);
+        assert_eq!(
+            AoeRule::Tankbuster
+                .check(&[(mt, Some(Role::Tank)), (r1, None)], Some(mt))
+                .offenders,
+            vec![r1]
+        );
+    }
+}
@@ -4,7 +4,11 @@ use bevy_egui::{
     EguiContexts,
 };
 
-use super::{despawn_all_arenas, spawn_arena, ArenaListing, ArenaMeta};
+use super::{
+    despawn_arena,
+    overlay::{OverlayLayer, ShownOverlays},
+    spawn_arena, switch_arena, ArenaListing, ArenaMeta, Arenas,
+};
 use crate::{
     asset::OptionalGlobalAsset,
     ui::{
@@ -14,23 +18,51 @@ use crate::{
     },
 };
 
-#[derive(Component, Debug)]
+#[derive(Component, Default, Debug)]
 #[require(InitWidget(|| widget!()))]
-pub struct ArenaMenu;
+pub struct ArenaMenu {
+    /// Whether picking an arena adds it alongside the current ones, rather than replacing them.
+    add: bool,
+}
 
 impl ArenaMenu {
     pub fn show(
-        WidgetCtx {
-            ns: _ns,
-            id: _id,
-            ui,
-        }: WidgetCtx,
+        WidgetCtx { ns: _ns, id, ui }: WidgetCtx,
+        mut menu_q: Query<&mut ArenaMenu>,
         arenas: OptionalGlobalAsset<ArenaListing>,
         assets: Res<Assets<ArenaMeta>>,
+        current: Arenas,
+        mut overlays: ResMut<ShownOverlays>,
         mut commands: Commands,
     ) {
+        let mut menu = menu_q.get_mut(id).unwrap();
         if let Some(ref listing) = arenas.option() {
-            Self::submenu(ui, listing, &assets, &mut commands);
+            ui.menu_button(listing.name.clone(), |ui| {
+                ui.checkbox(&mut menu.add, "Add to board");
+                let current = current.all();
+                if current.len() > 1 {
+                    ui.menu_button("Remove", |ui| {
+                        // Arenas are listed left to right, numbered so duplicates can be told apart.
+                        for (n, (id, arena)) in current.into_iter().enumerate() {
+                            let label = format!("{} #{}", arena.short_name, n + 1);
+                            if ui.button(label).clicked() {
+                                commands.queue(move |world: &mut World| despawn_arena(id, world));
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                }
+                ui.menu_button("Overlays", |ui| {
+                    for layer in enum_iterator::all::<OverlayLayer>() {
+                        let mut shown = overlays.is_shown(layer);
+                        if ui.checkbox(&mut shown, layer.name()).changed() {
+                            overlays.set_shown(layer, shown);
+                        }
+                    }
+                });
+                ui.separator();
+                Self::contents(ui, listing, &assets, menu.add, &mut commands);
+            });
         } else {
             ui.menu_button("Arenas", |ui| {
                 ui.label(RichText::new("Loading...").italics())
@@ -42,26 +74,40 @@ impl ArenaMenu {
         ui: &mut egui::Ui,
         listing: &ArenaListing,
         assets: &Assets<ArenaMeta>,
+        add: bool,
         commands: &mut Commands,
     ) {
         ui.menu_button(listing.name.clone(), |ui| {
-            for subdir in &listing.subdirs {
-                Self::submenu(ui, subdir, assets, commands);
-            }
-            if !listing.subdirs.is_empty() && !listing.contents.is_empty() {
-                ui.separator();
-            }
-            for handle in &listing.contents {
-                let Some(arena) = assets.get(handle) else {
-                    error!("arena listing's contents not fully loaded");
-                    continue;
-                };
-                if ui.button(arena.short_name.clone()).clicked() {
-                    commands.run_system_cached(despawn_all_arenas);
+            Self::contents(ui, listing, assets, add, commands);
+        });
+    }
+
+    fn contents(
+        ui: &mut egui::Ui,
+        listing: &ArenaListing,
+        assets: &Assets<ArenaMeta>,
+        add: bool,
+        commands: &mut Commands,
+    ) {
+        for subdir in &listing.subdirs {
+            Self::submenu(ui, subdir, assets, add, commands);
+        }
+        if !listing.subdirs.is_empty() && !listing.contents.is_empty() {
+            ui.separator();
+        }
+        for handle in &listing.contents {
+            let Some(arena) = assets.get(handle) else {
+                error!("arena listing's contents not fully loaded");
+                continue;
+            };
+            if ui.button(arena.short_name.clone()).clicked() {
+                if add {
                     commands.run_system_cached_with(spawn_arena, arena.clone());
+                } else {
+                    commands.run_system_cached_with(switch_arena, arena.clone());
                 }
             }
-        });
+        }
     }
 }
 
@@ -74,7 +120,7 @@ impl Plugin for ArenaMenuPlugin {
             Startup,
             |top: Single<Entity, With<TopMenu>>, mut commands: Commands| {
                 commands.entity(*top).with_child((
-                    ArenaMenu,
+                    ArenaMenu::default(),
                     UiSortKey(10),
                     Name::new("Arena Menu"),
                 ));
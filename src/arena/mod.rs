@@ -6,6 +6,7 @@ use std::{
 use avian2d::prelude::*;
 use bevy::{
     asset::{AssetLoader, ParseAssetPathError},
+    ecs::system::SystemParam,
     prelude::*,
 };
 #[cfg(feature = "dom")]
@@ -13,6 +14,7 @@ use component::{ArenaWebComponents, ARENA_COMPONENT_TAG};
 #[cfg(feature = "dom")]
 use custom_elements::CustomElement;
 use itertools::Itertools;
+use overlay::{Overlay, OverlayLayer, ShownOverlays};
 use serde::Deserialize;
 use thiserror::Error;
 
@@ -20,6 +22,7 @@ use crate::{
     asset::{AssetHookExt, AssetHookTarget, AssetListing, LifecycleExts, ListingExt},
     image::{DrawImage, DrawImageKind},
     shape::{ColliderFromShape, Shape},
+    view::ViewRotation,
     waymark::{Preset, Waymark},
     Layer,
 };
@@ -38,6 +41,8 @@ pub mod component {
     pub use super::component_dom::*;
 }
 
+pub mod overlay;
+
 /// The file extension of `Arena` files.
 const EXTENSION: &str = "arena.ron";
 /// The path, relative to the assets directory, to the directory where `Arena` files are stored.
@@ -77,6 +82,14 @@ pub struct ArenaMeta {
     pub offset: Vec2,
     /// The shape of the actual usuable arena surface, inside the (death)wall.
     pub shape: Shape,
+    /// Guides to draw over the arena.
+    #[serde(default)]
+    pub overlays: Vec<Overlay>,
+    /// The asset path of the arena file itself.
+    ///
+    /// This is not read from the asset file; it is filled in during loading.
+    #[serde(skip)]
+    pub asset_path: String,
 }
 
 #[derive(Default, Copy, Clone, Debug)]
@@ -110,93 +123,400 @@ impl AssetLoader for ArenaLoader {
             .asset_path()
             .resolve(&data.background_path)?
             .to_string();
+        data.asset_path = load_context.asset_path().to_string();
         Ok(data)
     }
 
     fn extensions(&self) -> &[&str] { &[EXTENSION] }
 }
 
-/// Component for the current arena.
-///
-/// Currently only one is allowed at a time.
+/// Component for an arena on the board.
 ///
-/// TODO: Make more than one allowed at a time?
+/// There can be several arenas on the board at once, laid out side by side.
+/// Everything placed on an arena is a child of it, and its game coordinates are relative to
+/// that arena's [`GameCoordOffset`].
 #[derive(Deref, Component, Reflect, Clone, Debug)]
 #[require(Transform)]
 #[cfg_attr(feature = "egui", require(Sprite, Visibility))]
 pub struct Arena(pub ArenaMeta);
 
-/// How big the viewport should be relative to the size of the arena.
+impl Arena {
+    /// Produces the offset to convert positions on this arena to and from game coordinates.
+    pub fn offset(&self) -> GameCoordOffset { GameCoordOffset(self.0.offset) }
+}
+
+/// How big the viewport should be relative to the size of the arenas.
 const ARENA_VIEWPORT_SCALE: f32 = 1.1;
 
 /// Z-coordinate of the arena background.
 const ARENA_BACKGROUND_Z: f32 = 0.0;
 
-/// This resource represents the global coordinate offset for
-/// game coordinates. It is updated whenever an arena is spawned.
+/// The gap between arenas laid out side by side, in yalms.
+const ARENA_SPACING: f32 = 5.0;
+
+/// The coordinate offset for game coordinates on a particular arena.
+///
+/// Positions relative to the arena's center are converted to game coordinates by this offset.
 ///
 /// It does not implement Default because (0,0) is probably the
 /// wrong offset.
-#[derive(Deref, Resource, Copy, Clone, Debug)]
+#[derive(Deref, Copy, Clone, Debug)]
 pub struct GameCoordOffset(pub Vec2);
 
+impl GameCoordOffset {
+    /// Converts a position in stratmat world coordinates into in-game (X, Z) coordinates.
+    pub fn world_to_game(self, pos: Vec2) -> Vec2 { Vec2::new(self.x + pos.x, self.y - pos.y) }
+
+    /// Converts in-game (X, Z) coordinates into a position in stratmat world coordinates.
+    pub fn game_to_world(self, pos: Vec2) -> Vec2 { Vec2::new(pos.x - self.x, self.y - pos.y) }
+
+    /// Moves a position on an arena with this offset to where it has the same game coordinates on
+    /// an arena with the `new` offset.
+    pub fn rebase(self, new: GameCoordOffset, pos: Vec2) -> Vec2 {
+        new.game_to_world(self.world_to_game(pos))
+    }
+}
+
 /// Event that is triggered when an arena is loaded, tageting the new arena.
 #[derive(Copy, Clone, Debug, Event, Reflect)]
 pub struct ArenaLoaded;
 
-/// Spawn an arena
+/// Spawn an arena to the right of any arenas already on the board,
+/// or at the origin if there are none.
+pub fn spawn_arena(
+    In(arena): In<ArenaMeta>,
+    arena_q: Query<(&Arena, &Transform)>,
+    mut commands: Commands,
+) {
+    let position = arena_q
+        .iter()
+        .map(|(arena, transform)| transform.translation.x + arena.size.x / 2.0)
+        .reduce(f32::max)
+        .map_or(Vec2::ZERO, |right| {
+            Vec2::new(Arena::next_to(right, &arena), 0.0)
+        });
+    Arena::spawn(&mut commands, arena, position);
+}
+
+/// [System] that replaces every arena on the board with a new arena.
 ///
-/// This includes resetting the camera and updating the [`GameCoordOffset`].
-fn spawn_arena(
+/// The new arena takes the place of the primary arena, and everything on the primary arena is moved
+/// onto it.
+pub fn switch_arena(
     In(arena): In<ArenaMeta>,
-    #[cfg(feature = "egui")] mut camera_q: Query<
-        &'static mut OrthographicProjection,
-        With<Camera2d>,
-    >,
-    asset_server: Res<AssetServer>,
+    arenas: Arenas,
+    transform_q: Query<&Transform>,
     mut commands: Commands,
 ) {
-    info!("Spawning new arena: {}", arena.name);
-    // FIXME: Single-camera assumption.
+    let position = arenas
+        .primary()
+        .and_then(|(old, _)| transform_q.get(old).ok())
+        .map_or(Vec2::ZERO, |transform| transform.translation.truncate());
+    let id = Arena::spawn(&mut commands, arena, position);
+    commands.queue(move |world: &mut World| replace_all_arenas(id, world));
+}
+
+impl Arena {
+    /// Spawns an arena centered on the given world position, producing its entity.
+    ///
+    /// Triggers [`ArenaLoaded`] on the new arena.
+    pub fn spawn(commands: &mut Commands, arena: ArenaMeta, position: Vec2) -> Entity {
+        info!("Spawning new arena: {}", arena.name);
+        let id = commands
+            .spawn((
+                Transform::from_translation(position.extend(ARENA_BACKGROUND_Z)),
+                CollisionLayers::new([Layer::DragSurface], [Layer::Dragged]),
+                PickingBehavior::IGNORE,
+            ))
+            .id();
+        Self::insert(&mut commands.entity(id), arena);
+        commands.trigger_targets(ArenaLoaded, id);
+        id
+    }
+
+    /// Produces the X-coordinate of the center of an arena laid out to the right of the edge at
+    /// `right`.
+    fn next_to(right: f32, arena: &ArenaMeta) -> f32 { right + ARENA_SPACING + arena.size.x / 2.0 }
+
+    /// [System] that lays the arenas on the board out side by side again, as [`spawn_arena`] does,
+    /// keeping their order and the position of the leftmost one.
+    pub fn lay_out(mut arena_q: Query<(&Arena, &mut Transform)>) {
+        let mut right = None;
+        for (arena, mut transform) in arena_q
+            .iter_mut()
+            .sorted_by(|(_, a), (_, b)| a.translation.x.total_cmp(&b.translation.x))
+        {
+            if let Some(right) = right {
+                transform.translation.x = Self::next_to(right, arena);
+            }
+            right = Some(transform.translation.x + arena.size.x / 2.0);
+        }
+    }
+
+    /// Inserts everything that depends on the arena file onto an arena entity.
+    fn insert(entity: &mut EntityCommands, arena: ArenaMeta) {
+        entity
+            .insert((
+                Name::new(format!("Arena Background ({})", arena.short_name)),
+                DrawImage::new(
+                    arena.background_path.clone().into(),
+                    arena.size,
+                    DrawImageKind::Sprite,
+                ),
+                arena.shape.clone(),
+                ColliderFromShape,
+            ))
+            .with_children(|parent| {
+                for overlay in &arena.overlays {
+                    overlay.spawn(parent, &arena);
+                }
+            })
+            .insert(Arena(arena));
+    }
+
+    /// [System] that updates arenas on the board when their arena file is modified.
+    ///
+    /// The arena is updated in place. Everything placed on it keeps its game coordinates, so it
+    /// moves on the arena if the offset has changed. If the size has changed, the arenas are laid
+    /// out again, and the camera is refit to them.
+    pub fn hot_reload(
+        mut events: EventReader<AssetEvent<ArenaMeta>>,
+        assets: Res<Assets<ArenaMeta>>,
+        arena_q: Query<(Entity, &Arena, Option<&Children>)>,
+        overlay_q: Query<(), With<OverlayLayer>>,
+        mut transform_q: Query<&mut Transform>,
+        mut commands: Commands,
+    ) {
+        let mut resized = false;
+        for ev in events.read() {
+            let AssetEvent::Modified { id } = *ev else {
+                continue;
+            };
+            let Some(meta) = assets.get(id) else {
+                continue;
+            };
+            for (entity, arena, children) in &arena_q {
+                if arena.asset_path != meta.asset_path {
+                    continue;
+                }
+                info!("Reloading arena: {}", meta.name);
+                let new_offset = GameCoordOffset(meta.offset);
+                for &child in children.into_iter().flatten() {
+                    if overlay_q.contains(child) {
+                        commands.entity(child).despawn_recursive();
+                    } else if meta.offset != arena.offset {
+                        let Ok(mut transform) = transform_q.get_mut(child) else {
+                            continue;
+                        };
+                        let pos = arena
+                            .offset()
+                            .rebase(new_offset, transform.translation.truncate());
+                        transform.translation = pos.extend(transform.translation.z);
+                    }
+                }
+                resized |= arena.size != meta.size;
+                Self::insert(&mut commands.entity(entity), meta.clone());
+            }
+        }
+        if resized {
+            commands.run_system_cached(Self::lay_out);
+        }
+    }
+
+    /// [System] that fits the camera to show every arena on the board.
+    ///
+    /// Runs whenever an arena is added, removed or changed, or the view is rotated.
+    ///
+    /// FIXME: Single-camera assumption.
     #[cfg(feature = "egui")]
-    {
+    pub fn fit_camera(
+        arena_q: Query<(&Arena, &Transform)>,
+        view: Res<ViewRotation>,
+        mut camera_q: Query<
+            (&mut OrthographicProjection, &mut Transform),
+            (With<Camera2d>, Without<Arena>),
+        >,
+    ) {
         use bevy::render::camera::ScalingMode;
-        camera_q.single_mut().scaling_mode = ScalingMode::AutoMin {
-            min_width: arena.size.x * ARENA_VIEWPORT_SCALE,
-            min_height: arena.size.y * ARENA_VIEWPORT_SCALE,
+        // The bounds are found in the rotated frame of the view, so that they line up with the
+        // edges of the screen.
+        let rotation = view.camera_rotation();
+        let Some(bounds) = arena_q
+            .iter()
+            .flat_map(|(arena, transform)| {
+                let rect = Rect::from_center_size(transform.translation.truncate(), arena.size);
+                [
+                    rect.min,
+                    rect.max,
+                    rect.min.with_x(rect.max.x),
+                    rect.max.with_x(rect.min.x),
+                ]
+            })
+            .map(|corner| {
+                let corner = (rotation.inverse() * corner.extend(0.0)).truncate();
+                Rect::from_corners(corner, corner)
+            })
+            .reduce(|a, b| a.union(b))
+        else {
+            return;
+        };
+        let Ok((mut projection, mut transform)) = camera_q.get_single_mut() else {
+            return;
         };
+        projection.scaling_mode = ScalingMode::AutoMin {
+            min_width: bounds.width() * ARENA_VIEWPORT_SCALE,
+            min_height: bounds.height() * ARENA_VIEWPORT_SCALE,
+        };
+        let center = rotation * bounds.center().extend(0.0);
+        transform.translation = center.with_z(transform.translation.z);
     }
-    let mut entity = commands.spawn((
-        Arena(arena.clone()),
-        Name::new("Arena Background"),
-        DrawImage::new(arena.background_path.into(), arena.size, DrawImageKind::Sprite),
-        Transform::from_xyz(0.0, 0.0, ARENA_BACKGROUND_Z),
-        arena.shape,
-        ColliderFromShape,
-        CollisionLayers::new([Layer::DragSurface], [Layer::Dragged]),
-        PickingBehavior::IGNORE,
-    ));
-    let id = entity.id();
+}
 
-    commands.insert_resource(GameCoordOffset(arena.offset));
-    commands.trigger_targets(ArenaLoaded, id);
+/// Replace every arena other than `new`, which is already spawned, with `new`.
+///
+/// Anything on the leftmost old arena is moved onto the new one, as with [`replace_arena`]. Any
+/// other arenas are despawned with [`despawn_arena`].
+pub fn replace_all_arenas(new: Entity, world: &mut World) {
+    let mut q = world.query_filtered::<(Entity, &Transform), With<Arena>>();
+    let mut old = q
+        .iter(world)
+        .filter(|&(id, _)| id != new)
+        .sorted_by(|(_, a), (_, b)| a.translation.x.total_cmp(&b.translation.x))
+        .map(|(id, _)| id)
+        .collect_vec()
+        .into_iter();
+    if let Some(primary) = old.next() {
+        replace_arena(primary, new, world);
+    }
+    for id in old {
+        despawn_arena(id, world);
+    }
+}
+
+/// Despawn a single arena.
+///
+/// Anything on it is left where it is; its overlays go with it.
+pub fn despawn_arena(id: Entity, world: &mut World) {
+    let children = world
+        .get::<Children>(id)
+        .map_or_else(Vec::new, |children| children.to_vec());
+    for child in children {
+        if !world.entity(child).contains::<OverlayLayer>() {
+            world.entity_mut(child).remove_parent_in_place();
+        }
+    }
+    world.entity_mut(id).despawn_recursive();
+}
+
+/// Replace one arena with another, already spawned, arena and despawn the old one.
+///
+/// Anything on the old arena is moved onto the new one, staying where it is; its overlays go with
+/// it.
+pub fn replace_arena(old: Entity, new: Entity, world: &mut World) {
+    // The new arena may not have had its transform propagated yet. It has no parent, so its
+    // global transform is just its transform.
+    let transform = *world.get::<Transform>(new).unwrap();
+    world
+        .entity_mut(new)
+        .insert(GlobalTransform::from(transform));
+    let children = world
+        .get::<Children>(old)
+        .map_or_else(Vec::new, |children| children.to_vec());
+    for child in children {
+        if !world.entity(child).contains::<OverlayLayer>() {
+            world.entity_mut(child).set_parent_in_place(new);
+        }
+    }
+    world.entity_mut(old).despawn_recursive();
 }
 
-/// Despawn all arenas.
-pub fn despawn_all_arenas(world: &mut World) {
-    let mut q = world.query_filtered::<Entity, With<Arena>>();
-    for id in q.iter(world).collect_vec() {
-        world.despawn(id);
+/// [`SystemParam`] for finding out which arena things are on.
+#[derive(SystemParam)]
+pub struct Arenas<'w, 's> {
+    arena_q: Query<'w, 's, (Entity, &'static Arena, &'static GlobalTransform)>,
+    parent_q: Query<'w, 's, &'static Parent>,
+    transform_q: Query<'w, 's, (&'static Transform, &'static GlobalTransform)>,
+}
+
+impl Arenas<'_, '_> {
+    /// Produces every arena on the board, from left to right.
+    pub fn all(&self) -> Vec<(Entity, &Arena)> {
+        self.arena_q
+            .iter()
+            .sorted_by(|(_, _, a), (_, _, b)| a.translation().x.total_cmp(&b.translation().x))
+            .map(|(id, arena, _)| (id, arena))
+            .collect()
+    }
+
+    /// Produces the primary arena, which is the leftmost one.
+    ///
+    /// This is the arena that things go on when there is no better choice.
+    pub fn primary(&self) -> Option<(Entity, &Arena)> { self.all().into_iter().next() }
+
+    /// Produces the arena that an entity is on: the entity itself if it is an arena,
+    /// or otherwise its nearest arena ancestor.
+    pub fn arena_of(&self, id: Entity) -> Option<(Entity, &Arena)> {
+        std::iter::once(id)
+            .chain(self.parent_q.iter_ancestors(id))
+            .find_map(|id| self.arena_q.get(id).ok())
+            .map(|(id, arena, _)| (id, arena))
+    }
+
+    /// Produces the transform of an entity relative to an arena.
+    ///
+    /// If the entity is directly on the arena, this is exactly its own [`Transform`].
+    pub fn transform_on(&self, id: Entity, arena: Entity) -> Option<Transform> {
+        let (transform, global) = self.transform_q.get(id).ok()?;
+        if self
+            .parent_q
+            .get(id)
+            .is_ok_and(|parent| parent.get() == arena)
+        {
+            return Some(*transform);
+        }
+        let (_, _, arena_global) = self.arena_q.get(arena).ok()?;
+        Some(global.reparented_to(arena_global))
     }
 }
 
-type ArenaListing = AssetListing<ArenaMeta>;
+pub type ArenaListing = AssetListing<ArenaMeta>;
+
+/// Produces every arena in the listing with the given FFXIV map ID.
+///
+/// Several arenas can share a map ID, such as the phases of an ultimate.
+pub fn arenas_for_map<'a>(
+    listing: &ArenaListing,
+    asset_server: &AssetServer,
+    assets: &'a Assets<ArenaMeta>,
+    map_id: u32,
+) -> Vec<&'a ArenaMeta> {
+    listing
+        .get_all(asset_server, assets)
+        .map(|(_, arena)| arena)
+        .filter(|arena| arena.map_id == map_id)
+        .collect()
+}
 
 #[derive(Debug, Clone, Default, Copy)]
 pub struct ArenaPlugin;
 
 impl Plugin for ArenaPlugin {
     fn build(&self, app: &mut App) {
+        #[cfg(feature = "egui")]
+        app.add_systems(
+            PostUpdate,
+            Arena::fit_camera
+                .run_if(
+                    (|q: Query<(), Changed<Arena>>| !q.is_empty())
+                        .or(any_component_removed::<Arena>)
+                        .or(resource_changed::<ViewRotation>),
+                )
+                .before(TransformSystem::TransformPropagate),
+        );
+        app.init_resource::<ShownOverlays>()
+            .register_type::<ShownOverlays>()
+            .add_systems(PostUpdate, overlay::update_visibility);
+        app.add_systems(Update, Arena::hot_reload);
         app.init_asset_with_lifecycle::<ArenaMeta>()
             .init_asset_listing::<ArenaMeta>()
             .register_type::<ArenaMeta>()
@@ -241,3 +561,115 @@ pub fn spawn_default_arena(mut commands: Commands, asset_server: Res<AssetServer
 }
 
 pub fn plugin() -> ArenaPlugin { ArenaPlugin }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn arena(size: f32) -> ArenaMeta {
+        ArenaMeta {
+            name: "Test Arena".into(),
+            short_name: "Test".into(),
+            map_id: 1,
+            background_path: String::new(),
+            size: Vec2::splat(size),
+            offset: Vec2::new(100.0, 100.0),
+            shape: Shape::Rectangle(Rectangle::from_length(size)),
+            overlays: vec![],
+            asset_path: String::new(),
+        }
+    }
+
+    #[test]
+    fn rebase_keeps_game_coordinates() {
+        let old = GameCoordOffset(Vec2::new(100.0, 100.0));
+        let new = GameCoordOffset(Vec2::new(0.0, 0.0));
+        let pos = Vec2::new(3.0, -4.0);
+        let rebased = old.rebase(new, pos);
+        assert_eq!(new.world_to_game(rebased), old.world_to_game(pos));
+        assert_eq!(rebased, Vec2::new(103.0, -104.0));
+        assert_eq!(old.rebase(old, pos), pos);
+    }
+
+    #[test]
+    fn lay_out_after_resize() {
+        let mut world = World::new();
+        let positions = [-10.0, 35.0, 80.0];
+        let ids = positions.map(|x| {
+            world
+                .spawn((Arena(arena(40.0)), Transform::from_xyz(x, 0.0, 0.0)))
+                .id()
+        });
+        world.entity_mut(ids[1]).insert(Arena(arena(60.0)));
+        world.run_system_cached(Arena::lay_out).unwrap();
+
+        let x = |id| world.get::<Transform>(id).unwrap().translation.x;
+        assert_eq!([x(ids[0]), x(ids[1]), x(ids[2])], [-10.0, 45.0, 100.0]);
+    }
+
+    #[test]
+    fn replace_arena_moves_things_on_it() {
+        let mut world = World::new();
+        let old = world
+            .spawn((
+                Arena(arena(40.0)),
+                Transform::from_xyz(10.0, 0.0, 0.0),
+                GlobalTransform::from_xyz(10.0, 0.0, 0.0),
+            ))
+            .id();
+        let player = world
+            .spawn((
+                Transform::from_xyz(3.0, 4.0, 0.0),
+                GlobalTransform::from_xyz(13.0, 4.0, 0.0),
+            ))
+            .set_parent(old)
+            .id();
+        let overlay = world.spawn(OverlayLayer::Grid).set_parent(old).id();
+        let new = world
+            .spawn((Arena(arena(60.0)), Transform::from_xyz(10.0, 0.0, 0.0)))
+            .id();
+
+        replace_arena(old, new, &mut world);
+        assert_eq!(world.get::<Parent>(player).map(Parent::get), Some(new));
+        assert_eq!(
+            world.get::<Transform>(player).unwrap().translation,
+            Vec3::new(3.0, 4.0, 0.0)
+        );
+        assert!(!world.entities().contains(old));
+        assert!(!world.entities().contains(overlay));
+    }
+
+    #[test]
+    fn replace_all_arenas_moves_things_on_the_primary() {
+        let mut world = World::new();
+        let [primary, extra] = [-10.0, 35.0].map(|x| {
+            world
+                .spawn((
+                    Arena(arena(40.0)),
+                    Transform::from_xyz(x, 0.0, 0.0),
+                    GlobalTransform::from_xyz(x, 0.0, 0.0),
+                ))
+                .id()
+        });
+        let player = world
+            .spawn((
+                Transform::from_xyz(3.0, 4.0, 0.0),
+                GlobalTransform::from_xyz(-7.0, 4.0, 0.0),
+            ))
+            .set_parent(primary)
+            .id();
+        let new = world
+            .spawn((Arena(arena(60.0)), Transform::from_xyz(-10.0, 0.0, 0.0)))
+            .id();
+
+        replace_all_arenas(new, &mut world);
+        assert_eq!(world.get::<Parent>(player).map(Parent::get), Some(new));
+        assert_eq!(
+            world.get::<Transform>(player).unwrap().translation,
+            Vec3::new(3.0, 4.0, 0.0)
+        );
+        assert!(!world.entities().contains(primary));
+        assert!(!world.entities().contains(extra));
+        assert!(world.entities().contains(new));
+    }
+}
@@ -0,0 +1,338 @@
+//! Arena overlays.
+//!
+//! Overlays are guides drawn over an arena, such as floor tiles or lines to the cardinals,
+//! so that they don't need to be baked into the background image.
+//! They are declared in the arena file, and each kind is drawn on its own layer,
+//! which can be shown or hidden.
+
+use std::{collections::BTreeSet, f32::consts::FRAC_PI_4};
+
+use bevy::prelude::*;
+#[cfg(feature = "egui")]
+use bevy_vector_shapes::{painter::ShapeConfig, shapes::ShapeBundle};
+use enum_iterator::Sequence;
+use serde::Deserialize;
+
+use super::{ArenaMeta, GameCoordOffset};
+#[cfg(feature = "egui")]
+use crate::label::{text_label, LABEL_FONT_SIZE};
+
+/// The Z-coordinate of overlays, relative to the arena.
+const OVERLAY_Z: f32 = 1.0;
+/// The thickness of overlay lines, in yalms.
+const LINE_THICKNESS: f32 = 0.08;
+/// The radius of the dot marking a landmark, in yalms.
+const LANDMARK_RADIUS: f32 = 0.4;
+
+/// A guide drawn over an arena.
+///
+/// All positions are in game coordinates, like [`ArenaMeta::offset`].
+/// Where a center is optional, it defaults to the center of the arena.
+///
+/// ```ron
+/// overlays: [
+///     Grid(tile_size: 10.0, tiles: (4, 4)),
+///     Lines(cardinals: true, intercardinals: true),
+///     Rings(radii: [5.0, 10.0, 15.0]),
+///     Landmark(name: "North Tower", position: (100.0, 85.0)),
+/// ]
+/// ```
+#[derive(Reflect, Clone, Debug, PartialEq, Deserialize)]
+pub enum Overlay {
+    /// A grid of square tiles.
+    Grid {
+        /// The length of the side of each tile.
+        tile_size: f32,
+        /// The number of tiles across (along X) and down (along Z).
+        tiles: UVec2,
+        #[serde(default)]
+        center: Option<Vec2>,
+    },
+    /// Lines through the center towards the cardinal and/or intercardinal directions.
+    Lines {
+        #[serde(default)]
+        cardinals: bool,
+        #[serde(default)]
+        intercardinals: bool,
+        /// The distance from the center to the end of each line.
+        /// Defaults to reaching the edges of the arena.
+        #[serde(default)]
+        length: Option<f32>,
+        #[serde(default)]
+        center: Option<Vec2>,
+    },
+    /// Concentric rings.
+    Rings {
+        radii: Vec<f32>,
+        #[serde(default)]
+        center: Option<Vec2>,
+    },
+    /// A named point of interest.
+    Landmark { name: String, position: Vec2 },
+}
+
+/// The layer an overlay is drawn on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Component, Reflect, Sequence)]
+pub enum OverlayLayer {
+    Grid,
+    Lines,
+    Rings,
+    Landmarks,
+}
+
+impl OverlayLayer {
+    /// Produces a name suitable for display.
+    pub fn name(self) -> &'static str {
+        match self {
+            OverlayLayer::Grid => "Grid",
+            OverlayLayer::Lines => "Guide Lines",
+            OverlayLayer::Rings => "Rings",
+            OverlayLayer::Landmarks => "Landmarks",
+        }
+    }
+
+    /// Produces the color that overlays on this layer are drawn with.
+    pub fn color(self) -> Color {
+        match self {
+            OverlayLayer::Grid => Color::srgba(1.0, 1.0, 1.0, 0.35),
+            OverlayLayer::Lines => Color::srgba(1.0, 1.0, 1.0, 0.5),
+            OverlayLayer::Rings => Color::srgba(1.0, 1.0, 1.0, 0.35),
+            OverlayLayer::Landmarks => Color::srgb(1.0, 0.84, 0.0),
+        }
+    }
+}
+
+/// The overlay layers that are currently shown.
+#[derive(Resource, Reflect, Clone, Debug)]
+pub struct ShownOverlays(pub BTreeSet<OverlayLayer>);
+
+impl Default for ShownOverlays {
+    fn default() -> Self { Self(enum_iterator::all().collect()) }
+}
+
+impl ShownOverlays {
+    pub fn is_shown(&self, layer: OverlayLayer) -> bool { self.0.contains(&layer) }
+
+    pub fn set_shown(&mut self, layer: OverlayLayer, shown: bool) {
+        if shown {
+            self.0.insert(layer);
+        } else {
+            self.0.remove(&layer);
+        }
+    }
+}
+
+impl Overlay {
+    pub fn layer(&self) -> OverlayLayer {
+        match self {
+            Overlay::Grid { .. } => OverlayLayer::Grid,
+            Overlay::Lines { .. } => OverlayLayer::Lines,
+            Overlay::Rings { .. } => OverlayLayer::Rings,
+            Overlay::Landmark { .. } => OverlayLayer::Landmarks,
+        }
+    }
+
+    /// Produces the center of the overlay, relative to the center of the arena.
+    pub fn center(&self, arena: &ArenaMeta) -> Vec2 {
+        let center = match self {
+            Overlay::Grid { center, .. }
+            | Overlay::Lines { center, .. }
+            | Overlay::Rings { center, .. } => center.unwrap_or(arena.offset),
+            Overlay::Landmark { position, .. } => *position,
+        };
+        GameCoordOffset(arena.offset).game_to_world(center)
+    }
+
+    /// Produces the line segments that make up the overlay, relative to its center.
+    pub fn segments(&self, arena: &ArenaMeta) -> Vec<(Vec2, Vec2)> {
+        match *self {
+            Overlay::Grid {
+                tile_size, tiles, ..
+            } => {
+                let half = tiles.as_vec2() * tile_size / 2.0;
+                let columns = (0..=tiles.x).map(|i| {
+                    let x = i as f32 * tile_size - half.x;
+                    (Vec2::new(x, -half.y), Vec2::new(x, half.y))
+                });
+                let rows = (0..=tiles.y).map(|i| {
+                    let y = i as f32 * tile_size - half.y;
+                    (Vec2::new(-half.x, y), Vec2::new(half.x, y))
+                });
+                columns.chain(rows).collect()
+            }
+            Overlay::Lines {
+                cardinals,
+                intercardinals,
+                length,
+                ..
+            } => {
+                let length = length.unwrap_or(arena.size.max_element() / 2.0);
+                (0..4)
+                    .filter(|i| {
+                        if i % 2 == 0 {
+                            cardinals
+                        } else {
+                            intercardinals
+                        }
+                    })
+                    .map(|i| {
+                        let end = Vec2::from_angle(i as f32 * FRAC_PI_4) * length;
+                        (-end, end)
+                    })
+                    .collect()
+            }
+            Overlay::Rings { .. } | Overlay::Landmark { .. } => vec![],
+        }
+    }
+
+    /// Spawns the entities that draw this overlay as a child of an arena.
+    pub fn spawn(&self, parent: &mut ChildBuilder, arena: &ArenaMeta) {
+        let layer = self.layer();
+        let center = self.center(arena);
+        #[cfg_attr(not(feature = "egui"), allow(unused_mut, unused_variables))]
+        let mut entity = parent.spawn((
+            Name::new(format!("Overlay ({})", layer.name())),
+            layer,
+            Transform::from_translation(center.extend(OVERLAY_Z)),
+            Visibility::default(),
+        ));
+
+        #[cfg(feature = "egui")]
+        entity.with_children(|parent| {
+            let config = ShapeConfig {
+                color: layer.color(),
+                thickness: LINE_THICKNESS,
+                hollow: true,
+                ..ShapeConfig::default_2d()
+            };
+            for (start, end) in self.segments(arena) {
+                parent.spawn(ShapeBundle::line(
+                    &config,
+                    start.extend(0.0),
+                    end.extend(0.0),
+                ));
+            }
+            match self {
+                Overlay::Rings { radii, .. } => {
+                    for &radius in radii {
+                        parent.spawn(ShapeBundle::circle(&config, radius));
+                    }
+                }
+                Overlay::Landmark { name, .. } => {
+                    parent.spawn(ShapeBundle::circle(
+                        &ShapeConfig {
+                            hollow: false,
+                            ..config
+                        },
+                        LANDMARK_RADIUS,
+                    ));
+                    parent
+                        .spawn(text_label(
+                            LABEL_FONT_SIZE,
+                            layer.color(),
+                            Vec2::new(0.0, 2.0 * LANDMARK_RADIUS),
+                        ))
+                        .insert(Text2d::new(name.clone()));
+                }
+                _ => {}
+            }
+        });
+    }
+}
+
+/// [System] that shows and hides overlays according to [`ShownOverlays`].
+pub fn update_visibility(
+    shown: Res<ShownOverlays>,
+    mut q: Query<(Ref<OverlayLayer>, &mut Visibility)>,
+) {
+    for (layer, mut visibility) in &mut q {
+        if shown.is_changed() || layer.is_added() {
+            *visibility = if shown.is_shown(*layer) {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::shape::Shape;
+
+    fn arena() -> ArenaMeta {
+        ArenaMeta {
+            name: "Test Arena".into(),
+            short_name: "Test".into(),
+            map_id: 1,
+            background_path: String::new(),
+            size: Vec2::splat(40.0),
+            offset: Vec2::new(100.0, 100.0),
+            shape: Shape::Rectangle(Rectangle::from_length(40.0)),
+            overlays: vec![],
+            asset_path: String::new(),
+        }
+    }
+
+    #[test]
+    fn parse_overlays() {
+        let overlays: Vec<Overlay> = ron::from_str(
+            r#"[
+                Grid(tile_size: 10.0, tiles: (4, 4)),
+                Lines(cardinals: true),
+                Rings(radii: [5.0, 10.0], center: Some((100.0, 90.0))),
+                Landmark(name: "North", position: (100.0, 85.0)),
+            ]"#,
+        )
+        .unwrap();
+        let layers = overlays.iter().map(Overlay::layer).collect::<Vec<_>>();
+        assert_eq!(
+            layers,
+            enum_iterator::all::<OverlayLayer>().collect::<Vec<_>>()
+        );
+        assert_eq!(overlays[1], Overlay::Lines {
+            cardinals: true,
+            intercardinals: false,
+            length: None,
+            center: None,
+        });
+    }
+
+    #[test]
+    fn overlay_geometry() {
+        let arena = arena();
+        let grid = Overlay::Grid {
+            tile_size: 10.0,
+            tiles: UVec2::new(4, 2),
+            center: None,
+        };
+        let segments = grid.segments(&arena);
+        assert_eq!(segments.len(), 5 + 3);
+        assert_eq!(
+            segments[0],
+            (Vec2::new(-20.0, -10.0), Vec2::new(-20.0, 10.0))
+        );
+        assert_eq!(segments[7], (Vec2::new(-20.0, 10.0), Vec2::new(20.0, 10.0)));
+
+        let lines = Overlay::Lines {
+            cardinals: true,
+            intercardinals: true,
+            length: None,
+            center: None,
+        };
+        let segments = lines.segments(&arena);
+        assert_eq!(segments.len(), 4);
+        assert!(segments
+            .iter()
+            .all(|(start, end)| (end.length() - 20.0).abs() < 1e-4 && *start == -*end));
+
+        // North in game coordinates is up in the world.
+        let landmark = Overlay::Landmark {
+            name: "North".into(),
+            position: Vec2::new(100.0, 85.0),
+        };
+        assert_eq!(landmark.center(&arena), Vec2::new(0.0, 15.0));
+    }
+}
@@ -39,7 +39,12 @@ impl Plugin for ColorPlugin {
         #[cfg(feature = "egui")]
         app.add_systems(
             PostUpdate,
-            (update_sprite_alpha, update_shape_alpha).after(propagate_alpha),
+            (
+                update_sprite_alpha,
+                update_shape_alpha,
+                update_material_alpha,
+            )
+                .after(propagate_alpha),
         );
     }
 }
@@ -58,6 +63,27 @@ pub fn update_shape_alpha(mut q: Query<(&mut ShapeFill, &ComputedAlpha)>) {
     }
 }
 
+/// [System] that applies [`ComputedAlpha`] to mesh materials, including the fresh ones that shapes
+/// get whenever their meshes are rebuilt.
+#[cfg(feature = "egui")]
+#[allow(clippy::type_complexity)]
+pub fn update_material_alpha(
+    q: Query<
+        (&MeshMaterial2d<ColorMaterial>, &ComputedAlpha),
+        Or<(
+            Changed<ComputedAlpha>,
+            Changed<MeshMaterial2d<ColorMaterial>>,
+        )>,
+    >,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (material, ComputedAlpha(alpha)) in &q {
+        if let Some(material) = materials.get_mut(material) {
+            material.color.set_alpha(*alpha)
+        }
+    }
+}
+
 // These two functions taken from Bevy. They're under the Apache license.
 // I'll fix the copyright properly later.
 #[allow(clippy::type_complexity)]
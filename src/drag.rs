@@ -109,24 +109,50 @@ fn drag_update_oob(
 }
 
 /// When the listener entity is dropped [`OutOfBounds`], despawn it and its children, otherwise undoes [`on_drag_start`].
+///
+/// An entity dropped onto a surface becomes a child of it, keeping its place in the world.
+/// If several surfaces overlap, the topmost is chosen.
 pub fn on_drag_end(
     event: Trigger<Pointer<DragEnd>>,
-    mut q: Query<(&mut CollisionLayers, Has<OutOfBounds>)>,
+    mut q: Query<
+        (
+            &mut CollisionLayers,
+            &CollidingEntities,
+            Option<&Parent>,
+            Has<OutOfBounds>,
+        ),
+        With<Dragged>,
+    >,
+    surface_q: Query<(&CollisionLayers, &GlobalTransform), Without<Dragged>>,
     mut commands: Commands,
 ) {
     let id = event.entity();
     debug!("ending drag on {id:?}");
-    let Ok((mut layers, oob)) = q.get_mut(id) else {
+    let Ok((mut layers, collisions, parent, oob)) = q.get_mut(id) else {
         debug!("but it doesn't exist");
         return;
     };
     if oob {
         debug!("{id:?} dropped out of bounds, despawning");
         commands.entity(id).despawn_recursive();
-    } else {
-        layers.memberships.remove(Layer::Dragged);
-        layers.filters.remove(Layer::DragSurface);
-        commands.entity(id).remove::<Dragged>();
+        return;
+    }
+
+    layers.memberships.remove(Layer::Dragged);
+    layers.filters.remove(Layer::DragSurface);
+    commands.entity(id).remove::<Dragged>();
+
+    let surface = collisions
+        .iter()
+        .filter_map(|&surface| Some((surface, surface_q.get(surface).ok()?)))
+        .filter(|(_, (layers, _))| layers.memberships.has_all(Layer::DragSurface))
+        .max_by(|(_, (_, a)), (_, (_, b))| a.translation().z.total_cmp(&b.translation().z))
+        .map(|(surface, _)| surface);
+    if let Some(surface) = surface {
+        if parent.map(Parent::get) != Some(surface) {
+            debug!("{id:?} dropped onto {surface:?}, reparenting");
+            commands.entity(id).set_parent_in_place(surface);
+        }
     }
 }
 
@@ -10,6 +10,7 @@ use bevy::{
         world::{unsafe_world_cell::UnsafeWorldCell, DeferredWorld},
     },
     prelude::*,
+    transform::helper::{ComputeGlobalTransformError, TransformHelper},
 };
 
 mod conflicts;
@@ -36,6 +37,20 @@ impl<C: Component> ChildFor<C> {
     }
 }
 
+/// Extension trait for [`TransformHelper`].
+pub trait TransformHelperExt {
+    /// Produces the position of an entity in world coordinates, from the latest [`Transform`]s of
+    /// it and its ancestors rather than its last propagated [`GlobalTransform`].
+    fn world_position(&self, id: Entity) -> Result<Vec2, ComputeGlobalTransformError>;
+}
+
+impl TransformHelperExt for TransformHelper<'_, '_> {
+    fn world_position(&self, id: Entity) -> Result<Vec2, ComputeGlobalTransformError> {
+        self.compute_global_transform(id)
+            .map(|transform| transform.translation().truncate())
+    }
+}
+
 pub trait EntityScope<'w> {
     fn id(&self) -> Entity;
     fn insert<B: Bundle>(&mut self, bundle: B) -> &mut Self;
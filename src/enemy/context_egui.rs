@@ -0,0 +1,120 @@
+//! Context panel for a single enemy.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use super::Enemy;
+use crate::{hitbox::RangeRings, player::job::RANGED_RANGE};
+
+/// The largest range ring that can be chosen, in yalms from the edge of the hitbox.
+const MAX_RING: f32 = 100.0;
+
+/// A window with controls for a single enemy, opened by right-clicking it.
+#[derive(Component, Reflect, Clone, Debug)]
+pub struct EnemyContextPanel {
+    pub enemy: Entity,
+    /// The range of the ring to be added.
+    ring: f32,
+}
+
+impl EnemyContextPanel {
+    pub fn new(enemy: Entity) -> Self {
+        Self {
+            enemy,
+            ring: RANGED_RANGE,
+        }
+    }
+
+    /// Observer that opens the panel for an enemy when it is right-clicked.
+    pub fn open_on_click(
+        ev: Trigger<Pointer<Click>>,
+        enemy_q: Query<(), With<Enemy>>,
+        panel_q: Query<&EnemyContextPanel>,
+        mut commands: Commands,
+    ) {
+        let id = ev.entity();
+        if ev.button != PointerButton::Secondary || !enemy_q.contains(id) {
+            return;
+        }
+        if panel_q.iter().all(|panel| panel.enemy != id) {
+            commands.spawn((Self::new(id), Name::new("Enemy Context Panel")));
+        }
+    }
+
+    /// [System] that draws each open panel, and closes those whose enemies are gone.
+    pub fn show(
+        mut ctx: EguiContexts,
+        mut panel_q: Query<(Entity, &mut EnemyContextPanel)>,
+        mut enemy_q: Query<(&Enemy, &mut RangeRings)>,
+        mut commands: Commands,
+    ) {
+        for (id, mut panel) in &mut panel_q {
+            let Ok((enemy, mut rings)) = enemy_q.get_mut(panel.enemy) else {
+                commands.entity(id).despawn();
+                continue;
+            };
+            let mut edited = rings.clone();
+            let mut open = true;
+            egui::Window::new(enemy.name.as_str())
+                .id(egui::Id::new(("Enemy Context Panel", panel.enemy)))
+                .open(&mut open)
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx.ctx_mut(), |ui| panel.edit_rings(&mut edited, ui));
+            rings.set_if_neq(edited);
+            if !open {
+                commands.entity(id).despawn();
+            }
+        }
+    }
+
+    /// Draws the controls to choose the enemy's range rings.
+    fn edit_rings(&mut self, rings: &mut RangeRings, ui: &mut egui::Ui) {
+        ui.label("Range Rings");
+        ui.checkbox(&mut rings.max_melee, "Max Melee");
+
+        let mut removed = None;
+        for (i, range) in rings.rings.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(range)
+                        .range(0.0..=MAX_RING)
+                        .speed(0.1)
+                        .suffix("y"),
+                );
+                if ui.small_button("✖").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            rings.rings.remove(i);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut self.ring)
+                    .range(0.0..=MAX_RING)
+                    .speed(0.1)
+                    .suffix("y"),
+            );
+            if ui.button("Add").clicked() {
+                rings.rings.push(self.ring);
+            }
+        });
+    }
+}
+
+/// Plugin for the enemy context panel.
+#[derive(Default, Copy, Clone, Debug)]
+pub struct EnemyContextPlugin;
+
+impl Plugin for EnemyContextPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EnemyContextPanel>()
+            .add_observer(EnemyContextPanel::open_on_click)
+            .add_systems(Update, EnemyContextPanel::show);
+    }
+}
+
+pub fn plugin() -> EnemyContextPlugin { EnemyContextPlugin }
@@ -0,0 +1,157 @@
+//! Enemies on the board.
+//!
+//! An enemy is a named [`Hitbox`] that can be dragged around and turned, like a player.
+//! Any number of them can be spawned, for fights with adds or several bosses.
+
+use avian2d::prelude::Collider;
+use bevy::{
+    color::palettes::css::GOLD,
+    ecs::{component::ComponentId, world::DeferredWorld},
+    prelude::*,
+};
+
+#[cfg(feature = "egui")]
+use crate::image::pick_sprite;
+use crate::{
+    drag::Draggable,
+    hitbox::{Hitbox, HitboxKind},
+    image::{DrawImage, DrawImageKind},
+    player::facing::{Facing, FacingArrow},
+    view::Upright,
+};
+
+#[cfg(feature = "egui")]
+mod context_egui;
+pub mod context {
+    #[cfg(feature = "egui")]
+    pub use super::context_egui::*;
+}
+#[cfg(feature = "egui")]
+mod window_egui;
+pub mod window {
+    #[cfg(feature = "egui")]
+    pub use super::window_egui::*;
+}
+
+/// The Z-coordinate of enemies. They are drawn under players.
+pub const ENEMY_Z: f32 = 10.0;
+/// The size of an enemy's icon, relative to its hitbox radius.
+const ICON_SIZE_RATIO: f32 = 0.8;
+/// The Z-coordinate of an enemy's icon, relative to the enemy.
+const ICON_Z: f32 = 1.0;
+/// The icon used for enemies that don't have their own.
+const DEFAULT_ICON_PATH: &str = "sprites/enemies/enemy.png";
+
+/// An enemy on the board.
+///
+/// The enemy's [`Hitbox`] is added from its kind, radius and color, and it turns to match its
+/// [`Facing`]. Its icon, if any, is drawn upright in the middle of the hitbox.
+#[derive(Clone, Debug, PartialEq)]
+#[derive(Component, Reflect)]
+#[require(Transform(|| Transform::from_xyz(0.0, 0.0, ENEMY_Z)), Visibility)]
+#[require(Draggable, Facing)]
+#[component(on_add = Self::on_add)]
+pub struct Enemy {
+    pub name: String,
+    pub kind: HitboxKind,
+    /// The radius of the enemy's hitbox, in yalms.
+    pub radius: f32,
+    pub color: Color,
+    /// The asset path of the enemy's icon.
+    pub icon: Option<String>,
+}
+
+impl Default for Enemy {
+    fn default() -> Self {
+        Self {
+            name: "Boss".into(),
+            kind: HitboxKind::Directional,
+            radius: 5.0,
+            color: GOLD.into(),
+            icon: None,
+        }
+    }
+}
+
+impl Enemy {
+    /// Produces the asset path of the enemy's icon, or of the default icon if it has none.
+    pub fn icon_asset_path(&self) -> &str { self.icon.as_deref().unwrap_or(DEFAULT_ICON_PATH) }
+
+    fn on_add(mut world: DeferredWorld, id: Entity, _: ComponentId) {
+        let enemy = world.get::<Enemy>(id).unwrap().clone();
+        let mut commands = world.commands();
+        let mut entity = commands.entity(id);
+        entity
+            .insert_if_new(Hitbox::new(enemy.kind, enemy.color, enemy.radius))
+            .insert((
+                Name::new(enemy.name.clone()),
+                Collider::circle(enemy.radius),
+            ));
+        #[cfg(feature = "egui")]
+        entity.with_child(pick_sprite(Vec2::splat(2.0 * enemy.radius)));
+        if let Some(icon) = enemy.icon {
+            entity.with_child((
+                Name::new("Enemy Icon"),
+                Upright,
+                DrawImage::new(
+                    icon.into(),
+                    Vec2::splat(ICON_SIZE_RATIO * enemy.radius),
+                    DrawImageKind::Sprite,
+                ),
+                Transform::from_xyz(0.0, 0.0, ICON_Z),
+            ));
+        }
+    }
+
+    /// [System] that turns enemies to match their facing, and moves their facing arrows to the
+    /// edges of their hitboxes.
+    pub fn update_rotations(
+        mut q: Query<(&Facing, &Hitbox, &mut Transform, Option<&Children>), With<Enemy>>,
+        mut arrow_q: Query<&mut Transform, (With<FacingArrow>, Without<Enemy>)>,
+    ) {
+        for (facing, hitbox, mut transform, children) in &mut q {
+            let rotation = facing.rotation();
+            if transform.rotation != rotation {
+                transform.rotation = rotation;
+            }
+            let mut arrows = arrow_q.iter_many_mut(children.into_iter().flatten());
+            while let Some(mut arrow) = arrows.fetch_next() {
+                if arrow.translation.y != hitbox.outer_radius {
+                    arrow.translation.y = hitbox.outer_radius;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Enemy>().add_systems(
+            PostUpdate,
+            Enemy::update_rotations
+                .before(FacingArrow::update_rotations)
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+pub fn plugin() -> EnemyPlugin { EnemyPlugin }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn enemy_icon() {
+        let boss = Enemy::default();
+        assert_eq!(boss.icon_asset_path(), DEFAULT_ICON_PATH);
+        let add = Enemy {
+            icon: Some("sprites/enemies/add.png".into()),
+            ..default()
+        };
+        assert_eq!(add.icon_asset_path(), "sprites/enemies/add.png");
+    }
+}
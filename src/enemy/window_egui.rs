@@ -0,0 +1,174 @@
+//! Enemy tray and associated code.
+
+use bevy::{
+    ecs::{component::ComponentId, system::SystemState, world::DeferredWorld},
+    prelude::*,
+};
+use bevy_egui::egui;
+use itertools::Itertools;
+
+use super::{Enemy, ENEMY_Z};
+use crate::{
+    ecs::{EntityWorldExts, NestedSystemExts},
+    hitbox::HitboxKind,
+    spawner::{self, panel::SpawnerPanel, Spawnable, Spawner},
+    ui::widget::{egui_context, Widget, WidgetSystemId},
+};
+
+const SIZE: f32 = 35.0;
+const SEP: f32 = 10.0;
+/// The largest hitbox radius that can be chosen, in yalms.
+const MAX_RADIUS: f32 = 30.0;
+
+impl Spawnable for Enemy {
+    const UNIQUE: bool = false;
+    const Z: f32 = ENEMY_Z;
+
+    // There is only ever one enemy spawner, for the template.
+    type SortKey = ();
+
+    fn size() -> Vec2 { Vec2::splat(SIZE) }
+    fn sep() -> Vec2 { Vec2::splat(SEP) }
+
+    fn spawner_name(&self) -> std::borrow::Cow<'static, str> { self.name.clone().into() }
+    fn sort_key(&self) {}
+
+    fn insert(&self, entity: &mut EntityCommands) { entity.insert(self.clone()); }
+}
+
+/// A window for setting up enemies and dragging them onto the board.
+#[derive(Debug, Default, Clone, PartialEq, Component, Reflect)]
+#[component(on_add = Self::on_add)]
+pub struct EnemyWindow {
+    /// The enemy that the spawner spawns.
+    template: Enemy,
+    /// The icon path being typed.
+    icon_path: String,
+}
+
+impl EnemyWindow {
+    /// [System] that draws the enemy window and handles events.
+    pub fn show(world: &mut World) {
+        let ctx = egui_context(world);
+        let mut state = SystemState::<(
+            Query<(Entity, &EnemyWindow)>,
+            Query<&Widget, With<SpawnerPanel<Enemy>>>,
+            Query<&Children>,
+        )>::new(world);
+
+        let ewin =
+            egui::Window::new("Enemies").default_width(4.0 * (Enemy::size() + Enemy::sep()).x);
+        ewin.show(&ctx, |ui| {
+            let (win_q, panel_q, parent_q) = state.get_mut(world);
+            let (win_id, win) = win_q.single();
+            let mut edited = win.clone();
+
+            let panel = panel_q
+                .iter_many(parent_q.children(win_id))
+                .copied()
+                .exactly_one()
+                .unwrap();
+            panel.show_world(world, ui);
+
+            state.apply(world);
+
+            ui.separator();
+            edited.edit_template(ui);
+            world
+                .get_mut::<EnemyWindow>(win_id)
+                .unwrap()
+                .set_if_neq(edited);
+        });
+    }
+
+    /// Draws the controls to set up the enemy that is spawned.
+    fn edit_template(&mut self, ui: &mut egui::Ui) {
+        let enemy = &mut self.template;
+        egui::Grid::new("Enemy Template").show(ui, |ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut enemy.name);
+            ui.end_row();
+
+            ui.label("Hitbox");
+            egui::ComboBox::from_id_salt("Enemy Hitbox Kind")
+                .selected_text(format!("{:?}", enemy.kind))
+                .show_ui(ui, |ui| {
+                    for kind in [HitboxKind::Directional, HitboxKind::Omni] {
+                        ui.selectable_value(&mut enemy.kind, kind, format!("{kind:?}"));
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Radius");
+            ui.add(
+                egui::DragValue::new(&mut enemy.radius)
+                    .range(0.5..=MAX_RADIUS)
+                    .speed(0.1)
+                    .suffix("y"),
+            );
+            ui.end_row();
+
+            ui.label("Color");
+            let srgb = enemy.color.to_srgba();
+            let mut rgb = [srgb.red, srgb.green, srgb.blue];
+            if ui.color_edit_button_rgb(&mut rgb).changed() {
+                enemy.color = Color::srgb(rgb[0], rgb[1], rgb[2]);
+            }
+            ui.end_row();
+
+            ui.label("Icon");
+            let edit = ui.text_edit_singleline(&mut self.icon_path);
+            if edit.lost_focus() {
+                let path = self.icon_path.trim();
+                enemy.icon = (!path.is_empty()).then(|| path.to_owned());
+            }
+            ui.end_row();
+        });
+    }
+
+    /// Setup the window.
+    ///
+    /// The spawner itself is added by [`EnemyWindow::update_spawners`].
+    pub fn on_add(mut world: DeferredWorld, id: Entity, _: ComponentId) {
+        world
+            .commands()
+            .entity(id)
+            .with_child(SpawnerPanel::<Enemy>::new());
+    }
+
+    /// [System] that rebuilds the enemy spawner whenever the template changes.
+    pub fn update_spawners(
+        win_q: Query<(&EnemyWindow, &Children), Changed<EnemyWindow>>,
+        panel_q: Query<Entity, With<SpawnerPanel<Enemy>>>,
+        mut commands: Commands,
+    ) {
+        for (win, children) in &win_q {
+            for panel in panel_q.iter_many(children) {
+                commands
+                    .entity(panel)
+                    .despawn_descendants()
+                    .with_child(Spawner::new(
+                        win.template.clone(),
+                        win.template.icon_asset_path().into(),
+                    ));
+            }
+        }
+    }
+}
+
+/// Plugin for the enemy window.
+#[derive(Default, Copy, Clone, Debug)]
+pub struct EnemyWindowPlugin;
+
+impl Plugin for EnemyWindowPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(spawner::plugin::<Enemy>())
+            .add_systems(Update, EnemyWindow::show)
+            .add_systems(PostUpdate, EnemyWindow::update_spawners)
+            .add_systems(Startup, |mut commands: Commands| {
+                commands.spawn((EnemyWindow::default(), Name::new("Enemies")));
+            });
+    }
+}
+
+pub fn plugin() -> EnemyWindowPlugin { EnemyWindowPlugin }
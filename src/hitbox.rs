@@ -5,21 +5,18 @@ use bevy::{
     ecs::{component::ComponentId, world::DeferredWorld},
     prelude::*,
 };
-use bevy_egui::{egui, EguiContexts};
-use bevy_vector_shapes::shapes::LineBundle;
 #[cfg(feature = "egui")]
 use bevy_vector_shapes::{
     painter::ShapeConfig,
     shapes::{DiscBundle, ShapeBundle},
 };
+use serde::{Deserialize, Serialize};
 
-#[cfg(feature = "egui")]
-use crate::ui::widget::{widget, InitWidget, WidgetCtx};
-use crate::ui::{menu::TopMenu, UiSortKey};
+use crate::player::job::MELEE_RANGE;
 
 /// The specific type of hitbox. Defines several important properties.
 #[derive(Default, Reflect, Copy, Clone, Debug)]
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum HitboxKind {
     /// A standard directional enemy hitbox, drawn as 3/4 of a circle with chevrons at the side.
     #[default]
@@ -30,7 +27,7 @@ pub enum HitboxKind {
 
 #[derive(Component, Reflect, Clone, Debug)]
 #[cfg_attr(feature = "egui", require(Visibility))]
-#[require(Transform)]
+#[require(Transform, RangeRings)]
 #[component(on_add = Self::on_add)]
 pub struct Hitbox {
     pub kind: HitboxKind,
@@ -50,8 +47,6 @@ const OUTER_CIRCLE_REAR_THICKNESS_RATIO: f32 = 0.006;
 const OUTER_CIRCLE_REAR_LIGHTNESS_SCALE: f32 = 0.65;
 /// The thickness of the outer circle, as a ratio of the inner circle radius.
 const INNER_CIRCLE_THICKNESS_RATIO: f32 = 0.01;
-/// The range of typical melee weaponskills.
-const MAX_MELEE_RANGE: f32 = 3.0;
 /// Lightness scaling factor to use when drawing the max melee radius.
 const MELEE_RANGE_LIGHTNESS_SCALE: f32 = 0.60;
 /// Alpha to use when drawing the max melee radius.
@@ -62,6 +57,10 @@ const MELEE_LINE_THICKNESS_RATIO: f32 = 0.004;
 const MELEE_LINE_LIGHTNESS_SCALE: f32 = 0.65;
 /// Alpha to use when drawing the max melee radius.
 const MELEE_LINE_ALPHA_SCALE: f32 = 1.0;
+/// Line thickness for other range rings.
+const RING_THICKNESS: f32 = 0.1;
+/// Lightness scaling factor to use when drawing other range rings.
+const RING_LIGHTNESS_SCALE: f32 = 0.8;
 
 impl Default for Hitbox {
     fn default() -> Self { Self::new(default(), bevy::color::palettes::css::SALMON.into(), 5.0) }
@@ -145,134 +144,137 @@ impl Hitbox {
         todo!();
     }
 
-    fn add_max_melee(q: Query<(Entity, &Hitbox), Without<MaxMelee>>, mut commands: Commands) {
-        for (id, hitbox) in &q {
-            let mut fill_color = Laba::from(hitbox.color);
-            fill_color.lightness *= MELEE_RANGE_LIGHTNESS_SCALE;
-            fill_color.alpha *= MELEE_RANGE_ALPHA_SCALE;
+    /// Produces the radius of the hitbox's max melee range.
+    pub fn max_melee_radius(&self) -> f32 { self.outer_radius + MELEE_RANGE }
+}
 
-            let mut line_color = Laba::from(hitbox.color);
-            line_color.lightness *= MELEE_LINE_LIGHTNESS_SCALE;
-            line_color.alpha *= MELEE_LINE_ALPHA_SCALE;
+/// The range rings drawn around a [`Hitbox`].
+#[derive(Component, Reflect, Clone, Debug, Default, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct RangeRings {
+    /// Whether to draw the max melee range, along with the lines between positionals.
+    #[serde(default)]
+    pub max_melee: bool,
+    /// Any other ranges to draw, in yalms from the edge of the hitbox.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rings: Vec<f32>,
+}
 
-            let radius = hitbox.outer_radius + MAX_MELEE_RANGE;
+impl RangeRings {
+    /// Returns true if no rings are drawn.
+    pub fn is_empty(&self) -> bool { !self.max_melee && self.rings.is_empty() }
 
-            commands
-                .entity(id)
-                .insert(MaxMelee)
-                .with_children(|parent| {
+    /// [System] that redraws the range rings of hitboxes whose rings or hitbox have changed.
+    #[cfg(feature = "egui")]
+    #[allow(clippy::type_complexity)]
+    pub fn update_drawings(
+        q: Query<
+            (Entity, &Hitbox, &RangeRings, Option<&Children>),
+            Or<(Changed<Hitbox>, Changed<RangeRings>)>,
+        >,
+        ring_q: Query<(), With<RangeRing>>,
+        mut commands: Commands,
+    ) {
+        for (id, hitbox, rings, children) in &q {
+            for &child in children.into_iter().flatten() {
+                if ring_q.contains(child) {
+                    commands.entity(child).despawn_recursive();
+                }
+            }
+            commands.entity(id).with_children(|parent| {
+                if rings.max_melee {
+                    Self::spawn_max_melee(hitbox, parent);
+                }
+                let mut ring_color = Laba::from(hitbox.color);
+                ring_color.lightness *= RING_LIGHTNESS_SCALE;
+                for &range in &rings.rings {
                     parent.spawn((
                         ShapeBundle::circle(
                             &ShapeConfig {
-                                color: fill_color.into(),
-                                transform: Transform::from_xyz(0.0, 0.0, 0.01),
+                                color: ring_color.into(),
+                                thickness: RING_THICKNESS,
+                                hollow: true,
                                 ..ShapeConfig::default_2d()
                             },
-                            radius,
+                            hitbox.outer_radius + range,
                         ),
-                        MaxMelee,
+                        RangeRing,
                     ));
-                    // Draw melee positional lines.
-                    if hitbox.is_directional() {
-                        // We need the axis-aligned coords of the points on the circle at 45, 135, etc. degrees.
-                        let coord = radius / SQRT_2;
-                        parent.spawn((
-                            ShapeBundle::line(
-                                &ShapeConfig {
-                                    color: line_color.into(),
-                                    thickness: MELEE_LINE_THICKNESS_RATIO * radius,
-                                    ..ShapeConfig::default_2d()
-                                },
-                                Vec3::new(coord, coord, 0.0),
-                                Vec3::new(-coord, -coord, 0.0),
-                            ),
-                            MaxMelee,
-                        ));
-                        parent.spawn((
-                            ShapeBundle::line(
-                                &ShapeConfig {
-                                    color: line_color.into(),
-                                    thickness: MELEE_LINE_THICKNESS_RATIO * hitbox.outer_radius,
-                                    ..ShapeConfig::default_2d()
-                                },
-                                Vec3::new(coord, -coord, 0.0),
-                                Vec3::new(-coord, coord, 0.0),
-                            ),
-                            MaxMelee,
-                        ));
-                    }
-                });
+                }
+            });
         }
     }
 
-    fn remove_max_melee(q: Query<(Entity, Has<Hitbox>), With<MaxMelee>>, mut commands: Commands) {
-        for (id, is_hitbox) in &q {
-            if is_hitbox {
-                commands.entity(id).remove::<MaxMelee>();
-            } else {
-                commands.entity(id).remove_parent().despawn();
-            }
-        }
-    }
-}
+    /// Draws the max melee range of a hitbox, and for directional hitboxes, the lines between
+    /// positionals.
+    #[cfg(feature = "egui")]
+    fn spawn_max_melee(hitbox: &Hitbox, parent: &mut ChildBuilder) {
+        let mut fill_color = Laba::from(hitbox.color);
+        fill_color.lightness *= MELEE_RANGE_LIGHTNESS_SCALE;
+        fill_color.alpha *= MELEE_RANGE_ALPHA_SCALE;
 
-#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
-/// Marker struct for melee range indicators.
-///
-/// For efficiency, this is used to mark both the entities used to draw the melee range
-/// and also the parent hitbox. They are distinguished by the presence/absence of [`Hitbox`].
-/// This lets us spawn shadows on newly-created entities without a child query.
-pub struct MaxMelee;
+        let mut line_color = Laba::from(hitbox.color);
+        line_color.lightness *= MELEE_LINE_LIGHTNESS_SCALE;
+        line_color.alpha *= MELEE_LINE_ALPHA_SCALE;
 
-/// Global setting (with widget) for whether to show max melee radii.
-// TODO: Make this not global?
-#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
-#[cfg_attr(feature = "egui", require(InitWidget(|| widget!())))]
-pub struct MeleeRangeToggle {
-    pub enabled: bool,
-}
+        let radius = hitbox.max_melee_radius();
 
-impl MeleeRangeToggle {
-    #[cfg(feature = "egui")]
-    pub fn show(
-        WidgetCtx { ns: _ns, id, ui }: WidgetCtx,
-        mut toggle: Query<&mut MeleeRangeToggle>,
-        mut ctx: EguiContexts,
-    ) {
-        ui.menu_button("Settings", |ui| {
-            ui.checkbox(&mut toggle.get_mut(id).unwrap().enabled, "Show Max Melee");
-        });
+        parent.spawn((
+            ShapeBundle::circle(
+                &ShapeConfig {
+                    color: fill_color.into(),
+                    transform: Transform::from_xyz(0.0, 0.0, 0.01),
+                    ..ShapeConfig::default_2d()
+                },
+                radius,
+            ),
+            RangeRing,
+        ));
+        // Draw melee positional lines.
+        if hitbox.is_directional() {
+            // We need the axis-aligned coords of the points on the circle at 45, 135, etc. degrees.
+            let coord = radius / SQRT_2;
+            parent.spawn((
+                ShapeBundle::line(
+                    &ShapeConfig {
+                        color: line_color.into(),
+                        thickness: MELEE_LINE_THICKNESS_RATIO * radius,
+                        ..ShapeConfig::default_2d()
+                    },
+                    Vec3::new(coord, coord, 0.0),
+                    Vec3::new(-coord, -coord, 0.0),
+                ),
+                RangeRing,
+            ));
+            parent.spawn((
+                ShapeBundle::line(
+                    &ShapeConfig {
+                        color: line_color.into(),
+                        thickness: MELEE_LINE_THICKNESS_RATIO * hitbox.outer_radius,
+                        ..ShapeConfig::default_2d()
+                    },
+                    Vec3::new(coord, -coord, 0.0),
+                    Vec3::new(-coord, coord, 0.0),
+                ),
+                RangeRing,
+            ));
+        }
     }
 }
 
+/// Marker component for the shapes drawing a hitbox's [`RangeRings`].
+#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
+pub struct RangeRing;
+
 /// Plugin for hitbox support
 #[derive(Default, Copy, Clone, Debug)]
 pub struct HitboxPlugin {}
 
 impl Plugin for HitboxPlugin {
     fn build(&self, app: &mut App) {
-        // TODO: Make this not depend on feature flags.
+        app.register_type::<RangeRings>();
         #[cfg(feature = "egui")]
-        app.add_systems(
-            Startup,
-            |top: Single<Entity, With<TopMenu>>, mut commands: Commands| {
-                commands.entity(*top).with_child((
-                    MeleeRangeToggle::default(),
-                    UiSortKey(1000),
-                    Name::new("Melee Range Toggle"),
-                ));
-            },
-        );
-        #[cfg(not(feature = "egui"))]
-        app.world_mut().spawn(MeleeRangeToggle::default());
-        app.add_systems(
-            PostUpdate,
-            (
-                Hitbox::add_max_melee.run_if(|toggle: Single<&MeleeRangeToggle>| toggle.enabled),
-                Hitbox::remove_max_melee
-                    .run_if(|toggle: Single<&MeleeRangeToggle>| !toggle.enabled),
-            ),
-        );
+        app.add_systems(PostUpdate, RangeRings::update_drawings);
     }
 }
 
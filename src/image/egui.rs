@@ -64,3 +64,16 @@ impl DrawImage {
         }
     }
 }
+
+/// Produces an invisible sprite of the given size, so that an entity drawn some other way, such
+/// as with shapes, can be picked.
+///
+/// Sprite alpha is set from [`ComputedAlpha`](crate::color::ComputedAlpha), so the sprite should
+/// go on a child of anything that has one, or it becomes visible.
+pub fn pick_sprite(size: Vec2) -> Sprite {
+    Sprite {
+        color: Color::NONE,
+        custom_size: Some(size),
+        ..default()
+    }
+}
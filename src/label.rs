@@ -0,0 +1,31 @@
+//! Text labels drawn on the board, such as the lengths of tethers.
+//!
+//! Labels are children of the thing they describe, and stay upright however the view is rotated.
+//! Their text is filled in by the systems of whatever owns them.
+
+use bevy::prelude::*;
+
+#[cfg(feature = "egui")]
+use crate::view::Upright;
+
+/// The font size used for labels, before scaling.
+pub const LABEL_FONT_SIZE: f32 = 32.0;
+/// The font size used for the smaller labels around players, before scaling.
+pub const SMALL_LABEL_FONT_SIZE: f32 = 28.0;
+/// The scale applied to labels, to bring them from pixels to yalms.
+pub const LABEL_SCALE: f32 = 0.03;
+/// The Z-coordinate of labels, relative to their parent.
+pub const LABEL_Z: f32 = 1.0;
+
+/// Produces an empty, upright text label of the given font size and color, at `position` relative
+/// to its parent.
+#[cfg(feature = "egui")]
+pub fn text_label(font_size: f32, color: Color, position: Vec2) -> impl Bundle {
+    (
+        Upright,
+        Text2d::default(),
+        TextFont::from_font_size(font_size),
+        TextColor(color),
+        Transform::from_translation(position.extend(LABEL_Z)).with_scale(Vec3::splat(LABEL_SCALE)),
+    )
+}
@@ -0,0 +1,97 @@
+//! Links between two entities, such as tethers and movements.
+//!
+//! A link is despawned along with either of its ends, and its [`LinkLabel`], if it has one, is kept
+//! midway between them. Links use the latest [`Transform`]s of their ends, so that they follow
+//! them while they are dragged.
+
+use std::marker::PhantomData;
+
+use bevy::{prelude::*, transform::helper::TransformHelper};
+
+use crate::ecs::TransformHelperExt;
+#[cfg(feature = "egui")]
+use crate::label::{text_label, LABEL_FONT_SIZE};
+
+/// A component linking two entities.
+pub trait Link: Component {
+    /// Produces the entities at either end of the link.
+    fn ends(&self) -> [Entity; 2];
+
+    /// Produces the world positions of the ends of the link, or `None` if either end is gone.
+    fn end_positions(&self, helper: &TransformHelper) -> Option<[Vec2; 2]> {
+        let [from, to] = self.ends().map(|end| helper.world_position(end));
+        Some([from.ok()?, to.ok()?])
+    }
+}
+
+/// Marker component for the label in the middle of a [`Link`].
+#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
+pub struct LinkLabel;
+
+/// Produces an empty label of the given color, to be spawned as a child of a [`Link`].
+#[cfg(feature = "egui")]
+pub fn link_label(color: Color) -> impl Bundle {
+    (LinkLabel, text_label(LABEL_FONT_SIZE, color, Vec2::ZERO))
+}
+
+/// [System] that despawns links that have lost an end.
+pub fn despawn_broken<L: Link>(
+    q: Query<(Entity, &L)>,
+    helper: TransformHelper,
+    mut commands: Commands,
+) {
+    for (id, link) in &q {
+        if link.end_positions(&helper).is_none() {
+            commands.entity(id).despawn_recursive();
+        }
+    }
+}
+
+/// [System] that moves the labels of links to the midpoints of their ends.
+#[cfg(feature = "egui")]
+pub fn update_labels<L: Link>(
+    q: Query<(&L, &Children)>,
+    mut transforms: ParamSet<(TransformHelper, Query<&mut Transform, With<LinkLabel>>)>,
+) {
+    for (link, children) in &q {
+        let Some([from, to]) = link.end_positions(&transforms.p0()) else {
+            continue;
+        };
+        let mut label_q = transforms.p1();
+        let mut labels = label_q.iter_many_mut(children);
+        while let Some(mut transform) = labels.fetch_next() {
+            transform.translation = from.midpoint(to).extend(transform.translation.z);
+        }
+    }
+}
+
+/// Plugin for the systems of one kind of [`Link`].
+#[derive(Copy, Clone, derive_more::Debug)]
+pub struct LinkPlugin<L> {
+    #[debug(skip)]
+    _phantom: PhantomData<L>,
+}
+
+impl<L> Default for LinkPlugin<L> {
+    fn default() -> Self {
+        Self {
+            _phantom: default(),
+        }
+    }
+}
+
+impl<L: Link> Plugin for LinkPlugin<L> {
+    fn build(&self, app: &mut App) {
+        app.register_type::<LinkLabel>().add_systems(
+            PostUpdate,
+            despawn_broken::<L>.before(TransformSystem::TransformPropagate),
+        );
+        #[cfg(feature = "egui")]
+        app.add_systems(
+            PostUpdate,
+            update_labels::<L>.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+pub fn plugin<L: Link>() -> LinkPlugin<L> { default() }
@@ -16,12 +16,7 @@ use asset::RootAssetPath;
 use avian2d::prelude::*;
 #[cfg(feature = "egui")]
 use bevy::winit::WinitSettings;
-use bevy::{
-    color::palettes::css::{GOLD, GOLDENROD},
-    input::InputPlugin,
-    log::LogPlugin,
-    prelude::*,
-};
+use bevy::{color::palettes::css::GOLDENROD, input::InputPlugin, log::LogPlugin, prelude::*};
 #[cfg(feature = "egui")]
 use bevy_egui::EguiPlugin;
 #[cfg(feature = "egui")]
@@ -29,10 +24,11 @@ use bevy_inspector_egui::quick::WorldInspectorPlugin;
 #[cfg(feature = "egui")]
 use bevy_vector_shapes::Shape2dPlugin;
 use clap::{ArgAction, Parser as _};
-use hitbox::Hitbox;
+use enemy::Enemy;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
+mod aoe;
 mod arena;
 mod asset;
 mod color;
@@ -41,14 +37,23 @@ mod component;
 mod debug;
 mod drag;
 mod ecs;
+mod enemy;
 mod hitbox;
 mod image;
+mod label;
+mod link;
+mod margin;
+mod movement;
 mod player;
+mod safe_zone;
 mod shape;
 mod spawner;
+mod strat;
 #[cfg(test)]
 mod testing;
+mod tether;
 mod ui;
+mod view;
 mod waymark;
 
 /// Collision layers.
@@ -81,6 +86,10 @@ struct Args {
     #[clap(long, env = "STRATMAT_LOG_COLLISION_EVENTS", action = ArgAction::Set, default_value_t = false)]
     /// Enable debug logging of collisions events
     log_collision_events: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    #[clap(long, env = "STRATMAT_WATCH_ASSETS", action = ArgAction::Set, default_value_t = cfg!(debug_assertions))]
+    /// Watch the assets directory and reload assets, such as arenas, when they change
+    watch_assets: bool,
     #[clap(long, short)]
     asset_root: Option<PathBuf>,
     #[clap(long, short)]
@@ -101,6 +110,8 @@ fn start(args: Args, #[cfg(feature = "egui")] primary_window: Window) -> eyre::R
 
     let mut default_plugins = DefaultPlugins.set(log_plugin).set(AssetPlugin {
         meta_check: bevy::asset::AssetMetaCheck::Never,
+        #[cfg(not(target_arch = "wasm32"))]
+        watch_for_changes_override: Some(args.watch_assets),
         ..default()
     });
     #[cfg(feature = "egui")]
@@ -129,12 +140,21 @@ fn start(args: Args, #[cfg(feature = "egui")] primary_window: Window) -> eyre::R
         )
         .add_plugins(asset::plugin())
         .add_plugins(arena::plugin())
+        .add_plugins(aoe::plugin())
         .add_plugins(color::plugin())
         .add_plugins(drag::plugin())
         .add_plugins(ecs::plugin())
+        .add_plugins(enemy::plugin())
+        .add_plugins(hitbox::plugin())
         .add_plugins(image::plugin())
+        .add_plugins(margin::plugin())
+        .add_plugins(movement::plugin())
         .add_plugins(player::plugin())
+        .add_plugins(safe_zone::plugin())
         .add_plugins(shape::plugin())
+        .add_plugins(strat::plugin())
+        .add_plugins(tether::plugin())
+        .add_plugins(view::plugin())
         .add_plugins(waymark::plugin())
         .add_systems(Startup, arena::spawn_default_arena);
 
@@ -142,16 +162,19 @@ fn start(args: Args, #[cfg(feature = "egui")] primary_window: Window) -> eyre::R
     app.add_plugins(EguiPlugin)
         .insert_resource(WinitSettings::desktop_app())
         .add_plugins(arena::menu::plugin())
+        .add_plugins(enemy::context::plugin())
+        .add_plugins(enemy::window::plugin())
+        .add_plugins(margin::menu::plugin())
         .add_plugins(Shape2dPlugin::default())
+        .add_plugins(player::context::plugin())
         .add_plugins(player::window::plugin())
+        .add_plugins(strat::menu::plugin())
+        .add_plugins(view::menu::plugin())
         .add_plugins(waymark::window::plugin())
         .add_plugins(ui::widget::plugin())
         .add_plugins(ui::menu::plugin())
         .add_systems(Startup, spawn_camera);
 
-    // Must come after egui::menu if egui is enabled.
-    app.add_plugins(hitbox::plugin());
-
     #[cfg(feature = "egui")]
     app.register_type::<bevy_vector_shapes::shapes::ShapeFill>()
         .register_type::<bevy_vector_shapes::shapes::ShapeMaterial>()
@@ -176,10 +199,7 @@ fn start(args: Args, #[cfg(feature = "egui")] primary_window: Window) -> eyre::R
         app.add_systems(PostUpdate, debug::log_events::<CollisionEnded>);
     }
 
-    app.world_mut().spawn((
-        Hitbox::new(hitbox::HitboxKind::Directional, GOLD.into(), 5.0),
-        Name::new("Hitbox"),
-    ));
+    app.world_mut().spawn(Enemy::default());
 
     app.run();
     Ok(())
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use bevy_egui::egui::{self, Color32, RichText};
+use itertools::Itertools;
+
+use super::Margin;
+use crate::{
+    player::Player,
+    ui::{
+        menu::TopMenu,
+        widget::{widget, InitWidget, WidgetCtx},
+        UiSortKey,
+    },
+};
+
+/// Top menu summarizing the margins of every player, tightest first.
+#[derive(Component, Default, Debug)]
+#[require(InitWidget(|| widget!()))]
+pub struct MarginMenu;
+
+impl MarginMenu {
+    pub fn show(WidgetCtx { ui, .. }: WidgetCtx, q: Query<(&Name, &Margin), With<Player>>) {
+        let margins = q
+            .iter()
+            .filter(|(_, margin)| margin.0.is_some())
+            .sorted_by(|(_, a), (_, b)| a.0.unwrap().total_cmp(&b.0.unwrap()))
+            .collect_vec();
+        let tight = margins
+            .iter()
+            .filter(|(_, margin)| margin.is_tight())
+            .count();
+        let title = if tight == 0 {
+            "Margins".to_owned()
+        } else {
+            format!("Margins ({tight} tight)")
+        };
+        ui.menu_button(title, |ui| {
+            if margins.is_empty() {
+                ui.label(RichText::new("Nothing to measure").italics());
+                return;
+            }
+            egui::Grid::new("Margins").show(ui, |ui| {
+                for (name, &margin) in margins {
+                    let [r, g, b, a] = margin.color().to_srgba().to_u8_array();
+                    ui.label(name.as_str());
+                    ui.label(
+                        RichText::new(margin.label())
+                            .color(Color32::from_rgba_unmultiplied(r, g, b, a)),
+                    );
+                    ui.end_row();
+                }
+            });
+        });
+    }
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct MarginMenuPlugin;
+
+impl Plugin for MarginMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Startup,
+            |top: Single<Entity, With<TopMenu>>, mut commands: Commands| {
+                commands.entity(*top).with_child((
+                    MarginMenu,
+                    UiSortKey(30),
+                    Name::new("Margin Menu"),
+                ));
+            },
+        );
+    }
+}
+
+pub fn plugin() -> MarginMenuPlugin { MarginMenuPlugin }
@@ -0,0 +1,259 @@
+//! Margin of error.
+//!
+//! A player's [`Margin`] is how far they can move before they're in the wrong place: hit by an AoE
+//! they should avoid, or out of somewhere they should stay, such as a stack they're taking or the
+//! arena itself. Margins are shown over each player, and the tightest ones are listed in the
+//! margin menu.
+
+use bevy::{
+    ecs::{component::ComponentId, world::DeferredWorld},
+    prelude::*,
+    transform::helper::TransformHelper,
+};
+use itertools::Itertools;
+
+#[cfg(feature = "egui")]
+use crate::label::{text_label, SMALL_LABEL_FONT_SIZE};
+use crate::{
+    aoe::{Aoe, AoeRule, HitBy, RuleCheck},
+    arena::Arena,
+    ecs::TransformHelperExt,
+    player::{PlayerSprite, PLAYER_SPRITE_SIZE},
+    shape::{Shape, ShapeAnchor},
+};
+
+#[cfg(feature = "egui")]
+mod menu_egui;
+pub mod menu {
+    #[cfg(feature = "egui")]
+    pub use super::menu_egui::*;
+}
+
+/// Margins below this many yalms are tight.
+pub const TIGHT_MARGIN: f32 = 1.0;
+
+/// The color of a comfortable margin.
+const SAFE_COLOR: Color = Color::srgb(0.4, 0.9, 0.4);
+/// The color of a tight margin.
+const TIGHT_COLOR: Color = Color::srgb(1.0, 0.75, 0.2);
+/// The color of a player who is already in the wrong place.
+const WRONG_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+
+/// How far a player can move in any direction before they're in the wrong place, in yalms.
+///
+/// Negative if they're already in the wrong place, in which case it's how far they are from
+/// getting out of the AoE or back onto the arena. `None` if there is nothing to measure against.
+#[derive(Component, Reflect, Default, Copy, Clone, Debug, PartialEq)]
+#[component(on_add = Self::on_add)]
+pub struct Margin(pub Option<f32>);
+
+/// Marker component for the label showing a player's [`Margin`].
+#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
+pub struct MarginLabel;
+
+impl Margin {
+    /// Returns true if the margin is below [`TIGHT_MARGIN`], including if it's negative.
+    pub fn is_tight(self) -> bool { self.0.is_some_and(|margin| margin < TIGHT_MARGIN) }
+
+    /// Produces the color to show the margin in.
+    pub fn color(self) -> Color {
+        match self.0 {
+            Some(margin) if margin < 0.0 => WRONG_COLOR,
+            _ if self.is_tight() => TIGHT_COLOR,
+            _ => SAFE_COLOR,
+        }
+    }
+
+    /// Produces a short description, like "2.5y", or nothing if there's no margin.
+    pub fn label(self) -> String {
+        self.0
+            .map(|margin| format!("{margin:.1}y"))
+            .unwrap_or_default()
+    }
+
+    /// Adds the label.
+    fn on_add(mut world: DeferredWorld, id: Entity, _: ComponentId) {
+        #[cfg(feature = "egui")]
+        world.commands().entity(id).with_child((
+            Name::new("Margin Label"),
+            MarginLabel,
+            text_label(
+                SMALL_LABEL_FONT_SIZE,
+                SAFE_COLOR,
+                Vec2::new(0.0, PLAYER_SPRITE_SIZE * 0.75),
+            ),
+        ));
+    }
+
+    /// [System] that measures the margin of everything with a [`HitBy`] against every AoE and
+    /// arena.
+    ///
+    /// AoEs that a player is welcome in, like stacks, don't limit them from outside; AoEs
+    /// anchored to the player, which move with them, don't limit them at all.
+    #[allow(clippy::type_complexity)]
+    pub fn update_margins(
+        mut q: Query<(Entity, &HitBy, Option<&PlayerSprite>, &mut Margin)>,
+        aoe_q: Query<
+            (
+                Entity,
+                &Shape,
+                Option<(&AoeRule, &RuleCheck)>,
+                Option<&ShapeAnchor>,
+            ),
+            With<Aoe>,
+        >,
+        arena_q: Query<(Entity, &Shape), With<Arena>>,
+        helper: TransformHelper,
+    ) {
+        let to_local = |id| {
+            helper
+                .compute_global_transform(id)
+                .ok()
+                .map(|transform| transform.affine().inverse())
+        };
+        let aoes = aoe_q
+            .iter()
+            .filter_map(|(id, shape, rule, anchor)| {
+                let target = anchor.map(|anchor| anchor.target);
+                Some((id, shape, rule, target, to_local(id)?))
+            })
+            .collect_vec();
+        let arenas = arena_q
+            .iter()
+            .filter_map(|(id, shape)| Some((shape, to_local(id)?)))
+            .collect_vec();
+
+        for (id, hits, sprite, mut margin) in &mut q {
+            let Ok(position) = helper.world_position(id) else {
+                continue;
+            };
+            let position = position.extend(0.0);
+            let role = sprite.and_then(|sprite| sprite.role());
+
+            let aoe_margins = aoes
+                .iter()
+                .filter(|&&(.., target, _)| target != Some(id))
+                .filter_map(|&(aoe, shape, rule, _, to_local)| {
+                    let distance =
+                        shape.edge_distance(to_local.transform_point3(position).truncate());
+                    if hits.0.contains(&aoe) {
+                        // An AoE with a rule is somewhere to stay, unless the player breaks it.
+                        let wanted = rule.is_some_and(|(_, check)| !check.offenders.contains(&id));
+                        Some(if wanted { distance } else { -distance })
+                    } else if rule.is_some_and(|(&rule, _)| rule.welcomes(role)) {
+                        None
+                    } else {
+                        Some(distance)
+                    }
+                });
+            // Players only need to be on one arena, so the best one counts.
+            let arena_margin = arenas
+                .iter()
+                .map(|(shape, to_local)| {
+                    let point = to_local.transform_point3(position).truncate();
+                    let distance = shape.edge_distance(point);
+                    if shape.contains(point) {
+                        distance
+                    } else {
+                        -distance
+                    }
+                })
+                .reduce(f32::max);
+
+            let new = aoe_margins.chain(arena_margin).reduce(f32::min);
+            margin.set_if_neq(Margin(new));
+        }
+    }
+
+    /// [System] that updates the labels of players whose margins have changed.
+    #[cfg(feature = "egui")]
+    pub fn update_labels(
+        q: Query<(&Margin, &Children), Changed<Margin>>,
+        mut label_q: Query<(&mut Text2d, &mut TextColor), With<MarginLabel>>,
+    ) {
+        for (&margin, children) in &q {
+            let mut labels = label_q.iter_many_mut(children);
+            while let Some((mut text, mut color)) = labels.fetch_next() {
+                text.0 = margin.label();
+                color.0 = margin.color();
+            }
+        }
+    }
+}
+
+/// Plugin for margins of error.
+#[derive(Default, Copy, Clone, Debug)]
+pub struct MarginPlugin;
+
+impl Plugin for MarginPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Margin>().add_systems(
+            PostUpdate,
+            Margin::update_margins.after(AoeRule::check_rules),
+        );
+        #[cfg(feature = "egui")]
+        app.add_systems(
+            PostUpdate,
+            Margin::update_labels.after(Margin::update_margins),
+        );
+    }
+}
+
+pub fn plugin() -> MarginPlugin { MarginPlugin }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::player::job::Role;
+
+    #[test]
+    fn margins() {
+        let mut app = App::new();
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            TransformPlugin,
+            crate::aoe::plugin(),
+            plugin(),
+        ));
+        let world = app.world_mut();
+        let player = world
+            .spawn((
+                HitBy::default(),
+                Margin::default(),
+                Transform::from_xyz(4.0, 0.0, 0.0),
+            ))
+            .id();
+        // The player is 2y from being hit by the circle, and inside the stack with 1y to spare.
+        world.spawn((
+            Aoe,
+            Shape::Circle(Circle::new(3.0)),
+            Transform::from_xyz(9.0, 0.0, 0.0),
+        ));
+        let stack = world
+            .spawn((
+                Aoe,
+                AoeRule::Stack(1),
+                Shape::Circle(Circle::new(5.0)),
+                Transform::default(),
+            ))
+            .id();
+        app.update();
+
+        let margin = |app: &App| app.world().get::<Margin>(player).unwrap().0.unwrap();
+        assert!((margin(&app) - 1.0).abs() < 1e-4);
+
+        // In a tankbuster without being a tank, the player must move 1y to get out of it.
+        app.world_mut()
+            .entity_mut(stack)
+            .insert(AoeRule::Tankbuster);
+        app.update();
+        assert!((margin(&app) + 1.0).abs() < 1e-4);
+
+        app.world_mut().entity_mut(player).insert(PlayerSprite {
+            job: None,
+            role: Some(Role::Tank),
+        });
+        app.update();
+        assert!((margin(&app) - 1.0).abs() < 1e-4);
+    }
+}
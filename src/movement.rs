@@ -0,0 +1,256 @@
+//! How long it takes players to move around the board.
+//!
+//! Whether a strat can be done at all often comes down to whether players can get where they need
+//! to be in time. A [`Movement`] is drawn as an arrow from a player to a draggable
+//! [`MoveTarget`], labelled with the distance and the time it takes to run it.
+
+use avian2d::prelude::Collider;
+use bevy::{
+    ecs::{component::ComponentId, world::DeferredWorld},
+    prelude::*,
+    transform::helper::TransformHelper,
+};
+#[cfg(feature = "egui")]
+use bevy_vector_shapes::{prelude::*, shapes::ShapeBundle};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    drag::Draggable,
+    link::{self, Link},
+};
+#[cfg(feature = "egui")]
+use crate::{
+    image::pick_sprite,
+    link::{link_label, LinkLabel},
+};
+
+/// The speed at which players run, in yalms per second.
+pub const RUN_SPEED: f32 = 6.0;
+/// The fraction by which Sprint increases a player's speed in combat.
+pub const SPRINT_BONUS: f32 = 0.3;
+
+/// The Z-coordinate of movement arrows. They are drawn under players but over tethers.
+const MOVEMENT_Z: f32 = 150.0;
+/// The Z-coordinate of move targets, so that they can be picked over the arrows.
+pub const TARGET_Z: f32 = 400.0;
+/// The radius of a move target.
+const TARGET_RADIUS: f32 = 0.4;
+/// The thickness of the lines of the movement arrow.
+const ARROW_THICKNESS: f32 = 0.12;
+/// The distance from the tip of the movement arrow to the back of its head.
+const ARROW_HEAD_LENGTH: f32 = 0.6;
+/// The color of movement arrows and targets.
+const ARROW_COLOR: Color = Color::srgb(0.6, 1.0, 0.5);
+
+/// Effects that change how fast a player moves.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[derive(Reflect, Serialize, Deserialize)]
+pub struct MoveModifiers {
+    /// Whether the player has Sprint up.
+    pub sprint: bool,
+    /// The fraction by which a slowing debuff, such as Heavy, reduces the player's speed.
+    pub slow: f32,
+}
+
+impl MoveModifiers {
+    /// Produces the player's speed with these modifiers, in yalms per second.
+    pub fn speed(self) -> f32 {
+        let sprint = if self.sprint { 1.0 + SPRINT_BONUS } else { 1.0 };
+        RUN_SPEED * sprint * (1.0 - self.slow.clamp(0.0, 1.0))
+    }
+}
+
+/// Produces the number of seconds it takes to run in a straight line from one position to another.
+///
+/// A player who can't move at all takes forever.
+pub fn travel_time(from: Vec2, to: Vec2, modifiers: MoveModifiers) -> f32 {
+    let distance = from.distance(to);
+    if distance == 0.0 {
+        return 0.0;
+    }
+    distance / modifiers.speed()
+}
+
+/// A movement from one entity, usually a player, to another, usually a [`MoveTarget`].
+#[derive(Component, Reflect, Copy, Clone, Debug, PartialEq)]
+#[require(Transform(|| Transform::from_xyz(0.0, 0.0, MOVEMENT_Z)), Visibility, MoveEstimate)]
+#[component(on_add = Self::on_add)]
+pub struct Movement {
+    pub from: Entity,
+    pub to: Entity,
+    pub modifiers: MoveModifiers,
+}
+
+impl Link for Movement {
+    fn ends(&self) -> [Entity; 2] { [self.from, self.to] }
+}
+
+/// The current length of a [`Movement`] and how long it takes.
+#[derive(Component, Reflect, Default, Copy, Clone, Debug, PartialEq)]
+pub struct MoveEstimate {
+    /// The distance moved, in yalms.
+    pub distance: f32,
+    /// The time taken, in seconds.
+    pub seconds: f32,
+}
+
+/// The part of a [`Movement`]'s arrow that a line draws.
+#[derive(Component, Reflect, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ArrowPart {
+    Shaft,
+    /// One side of the head, the left with -1 and the right with 1.
+    Head(i8),
+}
+
+/// A spot on the board that a player moves to, which can be dragged around.
+///
+/// Dragging it off the board removes it, and with it the [`Movement`].
+#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
+#[require(Transform(|| Transform::from_xyz(0.0, 0.0, TARGET_Z)), Visibility)]
+#[require(Draggable, Collider(|| Collider::circle(TARGET_RADIUS)))]
+#[component(on_add = Self::on_add)]
+pub struct MoveTarget;
+
+impl MoveTarget {
+    /// Adds the circle and a sprite for picking.
+    fn on_add(mut world: DeferredWorld, id: Entity, _: ComponentId) {
+        #[cfg(feature = "egui")]
+        world.commands().entity(id).with_children(|parent| {
+            parent.spawn(pick_sprite(Vec2::splat(2.0 * TARGET_RADIUS)));
+            parent.spawn(ShapeBundle::circle(
+                &ShapeConfig {
+                    color: ARROW_COLOR,
+                    thickness: ARROW_THICKNESS,
+                    hollow: true,
+                    ..ShapeConfig::default_2d()
+                },
+                TARGET_RADIUS,
+            ));
+        });
+    }
+}
+
+impl Movement {
+    pub fn new(from: Entity, to: Entity, modifiers: MoveModifiers) -> Self {
+        Self {
+            from,
+            to,
+            modifiers,
+        }
+    }
+
+    /// Adds the arrow and label.
+    fn on_add(mut world: DeferredWorld, id: Entity, _: ComponentId) {
+        #[cfg(feature = "egui")]
+        world.commands().entity(id).with_children(|parent| {
+            let config = ShapeConfig {
+                color: ARROW_COLOR,
+                thickness: ARROW_THICKNESS,
+                ..ShapeConfig::default_2d()
+            };
+            for part in [ArrowPart::Shaft, ArrowPart::Head(-1), ArrowPart::Head(1)] {
+                parent.spawn((part, ShapeBundle::line(&config, Vec3::ZERO, Vec3::ZERO)));
+            }
+            parent.spawn(link_label(ARROW_COLOR));
+        });
+    }
+
+    /// [System] that measures movements.
+    pub fn update_estimates(mut q: Query<(&Movement, &mut MoveEstimate)>, helper: TransformHelper) {
+        for (movement, mut estimate) in &mut q {
+            let Some([from, to]) = movement.end_positions(&helper) else {
+                continue;
+            };
+            estimate.set_if_neq(MoveEstimate {
+                distance: from.distance(to),
+                seconds: travel_time(from, to, movement.modifiers),
+            });
+        }
+    }
+
+    /// [System] that moves movement arrows to follow their ends.
+    #[cfg(feature = "egui")]
+    pub fn update_drawings(
+        q: Query<(&Movement, &MoveEstimate, &Children)>,
+        helper: TransformHelper,
+        mut line_q: Query<(&ArrowPart, &mut LineComponent)>,
+        mut label_q: Query<&mut Text2d, With<LinkLabel>>,
+    ) {
+        for (movement, estimate, children) in &q {
+            let Some([from, to]) = movement.end_positions(&helper) else {
+                continue;
+            };
+            let back = (from - to).normalize_or_zero() * ARROW_HEAD_LENGTH;
+
+            for &child in children {
+                if let Ok((part, mut line)) = line_q.get_mut(child) {
+                    let (start, end) = match *part {
+                        ArrowPart::Shaft => (from, to),
+                        ArrowPart::Head(side) => (to + back + back.perp() * f32::from(side), to),
+                    };
+                    line.start = start.extend(0.0);
+                    line.end = end.extend(0.0);
+                }
+                if let Ok(mut text) = label_q.get_mut(child) {
+                    text.0 = format!("{:.1}y, {:.1}s", estimate.distance, estimate.seconds);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct MovementPlugin;
+
+impl Plugin for MovementPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(link::plugin::<Movement>())
+            .register_type::<Movement>()
+            .register_type::<MoveEstimate>()
+            .register_type::<MoveTarget>()
+            .add_systems(
+                PostUpdate,
+                Movement::update_estimates.before(TransformSystem::TransformPropagate),
+            );
+        #[cfg(feature = "egui")]
+        app.add_systems(
+            PostUpdate,
+            Movement::update_drawings
+                .after(Movement::update_estimates)
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+pub fn plugin() -> MovementPlugin { MovementPlugin }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn travel_times() {
+        let from = Vec2::ZERO;
+        let to = Vec2::new(12.0, 0.0);
+        assert_eq!(travel_time(from, to, default()), 2.0);
+        assert_eq!(travel_time(from, from, default()), 0.0);
+
+        let sprint = MoveModifiers {
+            sprint: true,
+            ..default()
+        };
+        assert!(travel_time(from, to, sprint) < 2.0);
+
+        let heavy = MoveModifiers {
+            slow: 0.5,
+            ..default()
+        };
+        assert_eq!(travel_time(from, to, heavy), 4.0);
+
+        let bound = MoveModifiers {
+            slow: 1.0,
+            ..default()
+        };
+        assert_eq!(travel_time(from, to, bound), f32::INFINITY);
+    }
+}
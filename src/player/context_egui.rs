@@ -0,0 +1,299 @@
+//! Context panel for a single player.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use super::{
+    facing::Facing,
+    marker::{DebuffColor, StatusMarker, StatusMarkers, MAX_LIMIT_CUT},
+    Player,
+};
+use crate::{
+    hitbox::Hitbox,
+    movement::{MoveModifiers, MoveTarget, Movement, TARGET_Z},
+    tether::{Tether, TetherRule},
+};
+
+/// How far in front of a player a new movement goes, in yalms.
+const DEFAULT_MOVE_DISTANCE: f32 = 5.0;
+
+/// A window with controls for a single player, opened by right-clicking them.
+#[derive(Component, Reflect, Clone, Debug)]
+pub struct PlayerContextPanel {
+    pub player: Entity,
+    /// The custom text marker being typed.
+    text: String,
+    /// The entity chosen to be tethered to the player.
+    tether_target: Option<Entity>,
+}
+
+impl PlayerContextPanel {
+    pub fn new(player: Entity) -> Self {
+        Self {
+            player,
+            text: String::new(),
+            tether_target: None,
+        }
+    }
+
+    /// Observer that opens the panel for a player when they are right-clicked.
+    pub fn open_on_click(
+        ev: Trigger<Pointer<Click>>,
+        player_q: Query<(), With<Player>>,
+        panel_q: Query<&PlayerContextPanel>,
+        mut commands: Commands,
+    ) {
+        let id = ev.entity();
+        if ev.button != PointerButton::Secondary || !player_q.contains(id) {
+            return;
+        }
+        if panel_q.iter().all(|panel| panel.player != id) {
+            commands.spawn((Self::new(id), Name::new("Player Context Panel")));
+        }
+    }
+
+    /// [System] that draws each open panel, and closes those whose players are gone.
+    #[allow(clippy::type_complexity)]
+    pub fn show(
+        mut ctx: EguiContexts,
+        mut panel_q: Query<(Entity, &mut PlayerContextPanel)>,
+        mut player_q: Query<(&Player, &mut StatusMarkers, &GlobalTransform, &Facing)>,
+        mut tether_q: Query<(Entity, &mut Tether)>,
+        mut move_q: Query<(Entity, &mut Movement)>,
+        target_q: Query<(Entity, &Name), Or<(With<Player>, With<Hitbox>)>>,
+        mut commands: Commands,
+    ) {
+        for (id, mut panel) in &mut panel_q {
+            let Ok((player, mut markers, transform, &facing)) = player_q.get_mut(panel.player)
+            else {
+                commands.entity(id).despawn();
+                continue;
+            };
+            let mut edited = markers.clone();
+            let mut open = true;
+            egui::Window::new(player.slot.abbrev())
+                .id(egui::Id::new(("Player Context Panel", panel.player)))
+                .open(&mut open)
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx.ctx_mut(), |ui| {
+                    panel.edit_markers(&mut edited, ui);
+                    ui.separator();
+                    panel.edit_tethers(&mut tether_q, &target_q, &mut commands, ui);
+                    ui.separator();
+                    let ahead = transform.translation().truncate()
+                        + facing.direction() * DEFAULT_MOVE_DISTANCE;
+                    panel.edit_movements(&mut move_q, ahead, &mut commands, ui);
+                });
+            markers.set_if_neq(edited);
+            if !open {
+                commands.entity(id).despawn();
+            }
+        }
+    }
+
+    /// Draws the controls to toggle the player's status markers.
+    fn edit_markers(&mut self, markers: &mut StatusMarkers, ui: &mut egui::Ui) {
+        ui.label("Markers");
+        ui.horizontal(|ui| {
+            for marker in [
+                StatusMarker::Stack,
+                StatusMarker::Spread,
+                StatusMarker::Tether,
+            ] {
+                let mut shown = markers.contains(&marker);
+                if ui.checkbox(&mut shown, marker.name()).changed() {
+                    markers.toggle(marker);
+                }
+            }
+        });
+
+        let limit_cut = markers.limit_cut();
+        let mut selected = limit_cut;
+        egui::ComboBox::from_label("Limit Cut")
+            .selected_text(limit_cut.map_or_else(|| "None".into(), |n| n.to_string()))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut selected, None, "None");
+                for n in 1..=MAX_LIMIT_CUT {
+                    ui.selectable_value(&mut selected, Some(n), n.to_string());
+                }
+            });
+        if selected != limit_cut {
+            markers.set_limit_cut(selected);
+        }
+
+        ui.horizontal(|ui| {
+            for color in enum_iterator::all::<DebuffColor>() {
+                let marker = StatusMarker::Debuff(color);
+                let mut shown = markers.contains(&marker);
+                if ui.checkbox(&mut shown, color.name()).changed() {
+                    markers.toggle(marker);
+                }
+            }
+        });
+
+        ui.separator();
+        let texts = markers
+            .0
+            .iter()
+            .filter(|marker| matches!(marker, StatusMarker::Text(_)))
+            .cloned()
+            .collect::<Vec<_>>();
+        for marker in texts {
+            ui.horizontal(|ui| {
+                ui.label(marker.name());
+                if ui.small_button("✖").clicked() {
+                    markers.toggle(marker);
+                }
+            });
+        }
+        ui.horizontal(|ui| {
+            let edit = ui.text_edit_singleline(&mut self.text);
+            let entered = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            let text = self.text.trim();
+            if (ui.button("Add").clicked() || entered) && !text.is_empty() {
+                let marker = StatusMarker::Text(text.to_owned());
+                if !markers.contains(&marker) {
+                    markers.toggle(marker);
+                }
+                self.text.clear();
+            }
+        });
+    }
+
+    /// Draws the controls to add, change and remove the player's tethers.
+    #[allow(clippy::type_complexity)]
+    fn edit_tethers(
+        &mut self,
+        tether_q: &mut Query<(Entity, &mut Tether)>,
+        target_q: &Query<(Entity, &Name), Or<(With<Player>, With<Hitbox>)>>,
+        commands: &mut Commands,
+        ui: &mut egui::Ui,
+    ) {
+        let name_of = |id: Entity| target_q.get(id).map_or("?", |(_, name)| name.as_str());
+        ui.label("Tethers");
+        egui::Grid::new(("Tethers", self.player)).show(ui, |ui| {
+            for (id, mut tether) in tether_q.iter_mut() {
+                if !tether.connects(self.player) {
+                    continue;
+                }
+                ui.label(name_of(tether.other(self.player)));
+                let mut rule = tether.rule;
+                egui::ComboBox::from_id_salt(id)
+                    .selected_text(Self::rule_name(rule))
+                    .show_ui(ui, |ui| {
+                        let distance = rule.distance();
+                        for choice in [TetherRule::Stretch(distance), TetherRule::Within(distance)]
+                        {
+                            ui.selectable_value(&mut rule, choice, Self::rule_name(choice));
+                        }
+                    });
+                let mut distance = rule.distance();
+                ui.add(
+                    egui::DragValue::new(&mut distance)
+                        .range(0.0..=100.0)
+                        .speed(0.1)
+                        .suffix("y"),
+                );
+                rule.set_distance(distance);
+                if rule != tether.rule {
+                    tether.rule = rule;
+                }
+                if ui.small_button("✖").clicked() {
+                    commands.entity(id).despawn_recursive();
+                }
+                ui.end_row();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt(("Tether Target", self.player))
+                .selected_text(self.tether_target.map_or("Tether to…", name_of))
+                .show_ui(ui, |ui| {
+                    for (id, name) in target_q.iter().filter(|&(id, _)| id != self.player) {
+                        ui.selectable_value(&mut self.tether_target, Some(id), name.as_str());
+                    }
+                });
+            let add = ui.add_enabled(self.tether_target.is_some(), egui::Button::new("Add"));
+            if let (true, Some(target)) = (add.clicked(), self.tether_target.take()) {
+                commands.spawn((
+                    Tether::new(self.player, target, default()),
+                    Name::new("Tether"),
+                ));
+            }
+        });
+    }
+
+    /// Draws the controls to add, change and remove the player's movements.
+    ///
+    /// New movements go to `ahead`, from where their targets can be dragged into place.
+    fn edit_movements(
+        &self,
+        move_q: &mut Query<(Entity, &mut Movement)>,
+        ahead: Vec2,
+        commands: &mut Commands,
+        ui: &mut egui::Ui,
+    ) {
+        ui.label("Movement");
+        egui::Grid::new(("Movements", self.player)).show(ui, |ui| {
+            for (id, mut movement) in move_q.iter_mut() {
+                if movement.from != self.player {
+                    continue;
+                }
+                let mut modifiers = movement.modifiers;
+                ui.checkbox(&mut modifiers.sprint, "Sprint");
+                let mut slow = modifiers.slow * 100.0;
+                ui.add(
+                    egui::DragValue::new(&mut slow)
+                        .range(0.0..=100.0)
+                        .prefix("Slow ")
+                        .suffix("%"),
+                );
+                modifiers.slow = slow / 100.0;
+                if modifiers != movement.modifiers {
+                    movement.modifiers = modifiers;
+                }
+                if ui.small_button("✖").clicked() {
+                    commands.entity(id).despawn_recursive();
+                    commands.entity(movement.to).despawn_recursive();
+                }
+                ui.end_row();
+            }
+        });
+
+        if ui.button("Add").clicked() {
+            let target = commands
+                .spawn((
+                    MoveTarget,
+                    Name::new("Move Target"),
+                    Transform::from_translation(ahead.extend(TARGET_Z)),
+                ))
+                .id();
+            commands.spawn((
+                Movement::new(self.player, target, MoveModifiers::default()),
+                Name::new("Movement"),
+            ));
+        }
+    }
+
+    fn rule_name(rule: TetherRule) -> &'static str {
+        match rule {
+            TetherRule::Stretch(_) => "Stretch beyond",
+            TetherRule::Within(_) => "Stay within",
+        }
+    }
+}
+
+/// Plugin for the player context panel.
+#[derive(Default, Copy, Clone, Debug)]
+pub struct PlayerContextPlugin;
+
+impl Plugin for PlayerContextPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PlayerContextPanel>()
+            .add_observer(PlayerContextPanel::open_on_click)
+            .add_systems(Update, PlayerContextPanel::show);
+    }
+}
+
+pub fn plugin() -> PlayerContextPlugin { PlayerContextPlugin }
@@ -0,0 +1,188 @@
+//! Which way players are facing.
+//!
+//! Players are drawn upright however the view is rotated, so their facing can't be their
+//! [`Transform`]'s rotation. Instead it's kept in [`Facing`], and shown with an arrow.
+
+#[cfg(feature = "egui")]
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::{
+    ecs::{component::ComponentId, world::DeferredWorld},
+    prelude::*,
+};
+#[cfg(feature = "egui")]
+use bevy_vector_shapes::{painter::ShapeConfig, shapes::ShapeBundle};
+use serde::{Deserialize, Serialize};
+
+use crate::drag::Dragged;
+#[cfg(feature = "egui")]
+use crate::image::pick_sprite;
+
+/// The distance from the center of the player to the tip of the facing arrow.
+const ARROW_LENGTH: f32 = 1.8;
+/// The distance from the tip of the facing arrow to the back of its head.
+const ARROW_HEAD_LENGTH: f32 = 0.4;
+/// The thickness of the lines of the facing arrow.
+const ARROW_THICKNESS: f32 = 0.1;
+/// The radius of the handle at the tip of the arrow that rotates the player.
+const HANDLE_RADIUS: f32 = 0.3;
+/// The Z-coordinate of the facing arrow, relative to the player.
+const ARROW_Z: f32 = -1.0;
+/// The number of degrees a player turns for each line scrolled while dragging them.
+const SCROLL_DEGREES: f32 = 15.0;
+/// The number of pixels scrolled that count as one line.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// The direction a player is facing, as a compass heading in degrees clockwise from north.
+#[derive(Component, Reflect, Default, Copy, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[component(on_add = Self::on_add)]
+pub struct Facing(f32);
+
+impl Facing {
+    pub fn new(heading: f32) -> Self { Self(heading.rem_euclid(360.0)) }
+
+    /// Produces the facing pointing along a direction in world coordinates.
+    pub fn from_direction(direction: Vec2) -> Self {
+        Self::new(direction.x.atan2(direction.y).to_degrees())
+    }
+
+    /// Produces the compass heading, in degrees clockwise from north.
+    pub fn heading(self) -> f32 { self.0 }
+
+    /// Produces the unit vector pointing in this direction, in world coordinates.
+    pub fn direction(self) -> Vec2 {
+        let (sin, cos) = self.0.to_radians().sin_cos();
+        Vec2::new(sin, cos)
+    }
+
+    /// Produces the rotation that turns something facing north to face this way.
+    pub fn rotation(self) -> Quat { Quat::from_rotation_z(-self.0.to_radians()) }
+
+    /// Adds the facing arrow and its handle.
+    fn on_add(mut world: DeferredWorld, id: Entity, _: ComponentId) {
+        #[cfg(feature = "egui")]
+        world.commands().entity(id).with_children(|parent| {
+            let config = ShapeConfig {
+                color: Color::WHITE,
+                thickness: ARROW_THICKNESS,
+                ..ShapeConfig::default_2d()
+            };
+            let tip = Vec3::new(0.0, ARROW_LENGTH, 0.0);
+            let head = |side: f32| tip + Vec3::new(side, -1.0, 0.0) * ARROW_HEAD_LENGTH;
+            parent
+                .spawn((
+                    Name::new("Facing Arrow"),
+                    FacingArrow,
+                    Transform::from_xyz(0.0, 0.0, ARROW_Z),
+                    Visibility::default(),
+                ))
+                .with_children(|arrow| {
+                    arrow.spawn(ShapeBundle::line(&config, Vec3::ZERO, tip));
+                    arrow.spawn(ShapeBundle::line(&config, head(-1.0), tip));
+                    arrow.spawn(ShapeBundle::line(&config, head(1.0), tip));
+                    arrow
+                        .spawn((
+                            Name::new("Facing Handle"),
+                            ShapeBundle::circle(&config, HANDLE_RADIUS),
+                            pick_sprite(Vec2::splat(2.0 * HANDLE_RADIUS)),
+                        ))
+                        .insert(Transform::from_translation(tip))
+                        .observe(Self::on_handle_drag)
+                        .observe(|mut ev: Trigger<Pointer<DragStart>>| ev.propagate(false))
+                        .observe(|mut ev: Trigger<Pointer<DragEnd>>| ev.propagate(false));
+                });
+        });
+    }
+
+    /// Turns the player to face the cursor while their handle is dragged.
+    ///
+    /// Will panic if there is not exactly one camera.
+    #[cfg(feature = "egui")]
+    fn on_handle_drag(
+        mut ev: Trigger<Pointer<Drag>>,
+        parent_q: Query<&Parent>,
+        mut player_q: Query<(&mut Facing, &GlobalTransform)>,
+        camera_q: Single<(&Camera, &GlobalTransform)>,
+    ) {
+        // Don't let the player itself be dragged.
+        ev.propagate(false);
+        let Some((mut facing, transform)) = parent_q
+            .iter_ancestors(ev.entity())
+            .find_map(|id| player_q.get_mut(id).ok())
+        else {
+            return;
+        };
+        let (camera, camera_transform) = *camera_q;
+        let Ok(cursor) =
+            camera.viewport_to_world_2d(camera_transform, ev.pointer_location.position)
+        else {
+            return;
+        };
+        let direction = cursor - transform.translation().truncate();
+        if direction != Vec2::ZERO {
+            *facing = Facing::from_direction(direction);
+        }
+    }
+
+    /// [System] that turns players that are being dragged when the mouse wheel is scrolled.
+    #[cfg(feature = "egui")]
+    pub fn scroll_while_dragged(
+        mut wheel: EventReader<MouseWheel>,
+        mut q: Query<&mut Facing, With<Dragged>>,
+    ) {
+        let lines: f32 = wheel
+            .read()
+            .map(|ev| match ev.unit {
+                MouseScrollUnit::Line => ev.y,
+                MouseScrollUnit::Pixel => ev.y / PIXELS_PER_LINE,
+            })
+            .sum();
+        if lines == 0.0 {
+            return;
+        }
+        for mut facing in &mut q {
+            *facing = Facing::new(facing.0 + lines * SCROLL_DEGREES);
+        }
+    }
+}
+
+/// Marker component for the arrow showing which way a player is facing.
+#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
+pub struct FacingArrow;
+
+impl FacingArrow {
+    /// [System] that points facing arrows the way their players are facing.
+    ///
+    /// The arrow is a child of the player, which is kept upright, so this undoes the player's
+    /// own rotation.
+    pub fn update_rotations(
+        mut arrow_q: Query<(&Parent, &mut Transform), With<FacingArrow>>,
+        player_q: Query<(&Facing, &Transform), Without<FacingArrow>>,
+    ) {
+        for (parent, mut transform) in &mut arrow_q {
+            let Ok((facing, player_transform)) = player_q.get(parent.get()) else {
+                continue;
+            };
+            let rotation = player_transform.rotation.inverse() * facing.rotation();
+            if transform.rotation != rotation {
+                transform.rotation = rotation;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn facing_directions() {
+        let east = Facing::new(90.0);
+        assert!(east.direction().abs_diff_eq(Vec2::X, 1e-5));
+        assert!((east.rotation() * Vec3::Y).abs_diff_eq(Vec3::X, 1e-5));
+
+        let southwest = Facing::from_direction(Vec2::new(-1.0, -1.0));
+        assert!((southwest.heading() - 225.0).abs() < 1e-4);
+        assert_eq!(Facing::new(-90.0).heading(), 270.0);
+    }
+}
@@ -1,8 +1,80 @@
 use bevy::prelude::*;
 use derive_more::derive::Display;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
 
+/// The range of typical melee weaponskills, in yalms.
+pub const MELEE_RANGE: f32 = 3.0;
+/// The range of typical ranged attacks and spells, in yalms.
+pub const RANGED_RANGE: f32 = 25.0;
+
+/// The role a job plays in a party.
 #[derive(Copy, Clone, Debug, Hash, PartialOrd, Ord, PartialEq, Eq)]
-#[derive(Reflect, Display)]
+#[derive(Reflect, Serialize, Deserialize, Sequence)]
+pub enum Role {
+    Tank,
+    PureHealer,
+    BarrierHealer,
+    Melee,
+    PhysicalRanged,
+    Caster,
+    /// Limited jobs, which can't join a normal party.
+    Limited,
+}
+
+impl Role {
+    /// Produces a name suitable for display.
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Tank => "Tank",
+            Role::PureHealer => "Pure Healer",
+            Role::BarrierHealer => "Barrier Healer",
+            Role::Melee => "Melee",
+            Role::PhysicalRanged => "Physical Ranged",
+            Role::Caster => "Caster",
+            Role::Limited => "Limited",
+        }
+    }
+
+    pub fn is_tank(self) -> bool { self == Role::Tank }
+
+    pub fn is_healer(self) -> bool { matches!(self, Role::PureHealer | Role::BarrierHealer) }
+
+    pub fn is_dps(self) -> bool {
+        matches!(self, Role::Melee | Role::PhysicalRanged | Role::Caster)
+    }
+
+    /// Produces true if jobs with this role have positional attacks.
+    pub fn has_positionals(self) -> bool { self == Role::Melee }
+
+    /// Produces the typical range of this role's attacks, in yalms.
+    pub fn attack_range(self) -> f32 {
+        match self {
+            Role::Tank | Role::Melee => MELEE_RANGE,
+            Role::PureHealer
+            | Role::BarrierHealer
+            | Role::PhysicalRanged
+            | Role::Caster
+            | Role::Limited => RANGED_RANGE,
+        }
+    }
+
+    /// Produces the asset path for the generic icon for this role, for players whose job isn't
+    /// known.
+    pub fn icon_asset_path(self) -> &'static str {
+        match self {
+            Role::Tank => "sprites/jobs/tank.png",
+            Role::PureHealer | Role::BarrierHealer => "sprites/jobs/healer.png",
+            Role::Melee => "sprites/jobs/melee.png",
+            Role::PhysicalRanged => "sprites/jobs/range.png",
+            Role::Caster => "sprites/jobs/caster.png",
+            Role::Limited => "sprites/jobs/dps.png",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Reflect, Display, Serialize, Deserialize, Sequence)]
 pub enum Job {
     // Tanks
     Paladin,
@@ -74,6 +146,25 @@ impl Job {
         }
     }
 
+    pub fn role(self) -> Role {
+        use Job::*;
+        match self {
+            Paladin | Warrior | DarkKnight | Gunbreaker => Role::Tank,
+            WhiteMage | Astrologian => Role::PureHealer,
+            Scholar | Sage => Role::BarrierHealer,
+            Monk | Dragoon | Ninja | Samurai | Reaper | Viper => Role::Melee,
+            Bard | Machinist | Dancer => Role::PhysicalRanged,
+            BlackMage | Summoner | RedMage | Pictomancer | Fisher => Role::Caster,
+            BlueMage | Beastmaster => Role::Limited,
+        }
+    }
+
+    /// Produces true if this job has positional attacks.
+    pub fn has_positionals(self) -> bool { self.role().has_positionals() }
+
+    /// Produces the typical range of this job's attacks, in yalms.
+    pub fn attack_range(self) -> f32 { self.role().attack_range() }
+
     pub fn icon_asset_path(self) -> &'static str {
         use Job::*;
         match self {
@@ -106,3 +197,23 @@ impl Job {
 
     pub fn none_asset_path() -> &'static str { "sprites/jobs/none.png" }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn job_roles() {
+        let roles = enum_iterator::all::<Job>()
+            .map(Job::role)
+            .collect::<Vec<_>>();
+        assert_eq!(roles.iter().filter(|r| r.is_tank()).count(), 4);
+        assert_eq!(roles.iter().filter(|r| r.is_healer()).count(), 4);
+
+        assert!(Job::Reaper.has_positionals());
+        assert!(!Job::Paladin.has_positionals());
+        assert_eq!(Job::Paladin.attack_range(), MELEE_RANGE);
+        assert_eq!(Job::Scholar.role(), Role::BarrierHealer);
+        assert_eq!(Job::Dancer.attack_range(), RANGED_RANGE);
+    }
+}
@@ -0,0 +1,270 @@
+//! Status markers over players' heads.
+//!
+//! Mechanics are usually telegraphed by a marker over a player's head, such as a stack marker or
+//! a limit cut number, or by a debuff. A player's markers are kept in [`StatusMarkers`], and drawn
+//! as a row of icons above them.
+
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+
+use super::PLAYER_SPRITE_SIZE;
+use crate::image::{DrawImage, DrawImageKind};
+#[cfg(feature = "egui")]
+use crate::label::{text_label, LABEL_FONT_SIZE};
+
+/// The size of a marker icon.
+const MARKER_SIZE: f32 = 1.2;
+/// The gap between marker icons.
+const MARKER_SEP: f32 = 0.15;
+/// The Z-coordinate of marker icons, relative to the player.
+const MARKER_Z: f32 = 1.0;
+
+/// The highest limit cut number.
+pub const MAX_LIMIT_CUT: u8 = 8;
+
+/// A marker shown over a player's head.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Reflect, Serialize, Deserialize)]
+pub enum StatusMarker {
+    /// The player is the target of a stack.
+    Stack,
+    /// The player must spread.
+    Spread,
+    /// The player is tethered.
+    Tether,
+    /// The player's limit cut number, from 1 to [`MAX_LIMIT_CUT`].
+    LimitCut(u8),
+    /// A debuff, identified by its color.
+    Debuff(DebuffColor),
+    /// Any other marker, shown as text.
+    Text(String),
+}
+
+impl StatusMarker {
+    /// Produces a name suitable for display.
+    pub fn name(&self) -> Cow<'static, str> {
+        match self {
+            StatusMarker::Stack => "Stack".into(),
+            StatusMarker::Spread => "Spread".into(),
+            StatusMarker::Tether => "Tether".into(),
+            StatusMarker::LimitCut(n) => format!("Limit Cut {n}").into(),
+            StatusMarker::Debuff(color) => format!("{} Debuff", color.name()).into(),
+            StatusMarker::Text(text) => text.clone().into(),
+        }
+    }
+
+    /// Produces the path to the marker's icon, if it has one.
+    ///
+    /// Custom text markers, and limit cut numbers that are out of range, don't have an icon.
+    pub fn asset_path(&self) -> Option<&'static str> {
+        const LIMIT_CUT: [&str; MAX_LIMIT_CUT as usize] = [
+            "sprites/markers/limit_cut_1.png",
+            "sprites/markers/limit_cut_2.png",
+            "sprites/markers/limit_cut_3.png",
+            "sprites/markers/limit_cut_4.png",
+            "sprites/markers/limit_cut_5.png",
+            "sprites/markers/limit_cut_6.png",
+            "sprites/markers/limit_cut_7.png",
+            "sprites/markers/limit_cut_8.png",
+        ];
+        match self {
+            StatusMarker::Stack => Some("sprites/markers/stack.png"),
+            StatusMarker::Spread => Some("sprites/markers/spread.png"),
+            StatusMarker::Tether => Some("sprites/markers/tether.png"),
+            StatusMarker::LimitCut(n) => LIMIT_CUT.get(usize::from(*n).checked_sub(1)?).copied(),
+            StatusMarker::Debuff(_) => Some("sprites/markers/debuff.png"),
+            StatusMarker::Text(_) => None,
+        }
+    }
+
+    /// Produces the color that the marker's icon is tinted with.
+    pub fn tint(&self) -> Color {
+        match self {
+            StatusMarker::Debuff(color) => color.color(),
+            _ => Color::WHITE,
+        }
+    }
+
+    /// Spawns the icon for this marker as a child of a player, at the given position.
+    fn spawn_icon(&self, parent: &mut ChildBuilder, position: Vec2) {
+        let transform = Transform::from_translation(position.extend(MARKER_Z));
+        let mut icon = parent.spawn((
+            Name::new(self.name()),
+            MarkerIcon,
+            transform,
+            Visibility::default(),
+        ));
+        if let Some(path) = self.asset_path() {
+            // Inserted before the image, so that the tint is kept when it loads.
+            #[cfg(feature = "egui")]
+            icon.insert(Sprite {
+                color: self.tint(),
+                ..default()
+            });
+            icon.insert(DrawImage::new(
+                path.into(),
+                Vec2::splat(MARKER_SIZE),
+                DrawImageKind::Sprite,
+            ));
+        } else {
+            #[cfg(feature = "egui")]
+            icon.insert(text_label(LABEL_FONT_SIZE, self.tint(), position))
+                .insert(Text2d::new(self.name()));
+        }
+    }
+}
+
+/// The color of a debuff marker.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Reflect, Serialize, Deserialize, Sequence)]
+pub enum DebuffColor {
+    Red,
+    Blue,
+    Yellow,
+    Green,
+    Purple,
+}
+
+impl DebuffColor {
+    pub fn name(self) -> &'static str {
+        match self {
+            DebuffColor::Red => "Red",
+            DebuffColor::Blue => "Blue",
+            DebuffColor::Yellow => "Yellow",
+            DebuffColor::Green => "Green",
+            DebuffColor::Purple => "Purple",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            DebuffColor::Red => Color::srgb(0.9, 0.2, 0.2),
+            DebuffColor::Blue => Color::srgb(0.25, 0.45, 0.95),
+            DebuffColor::Yellow => Color::srgb(0.95, 0.85, 0.2),
+            DebuffColor::Green => Color::srgb(0.3, 0.8, 0.3),
+            DebuffColor::Purple => Color::srgb(0.65, 0.3, 0.9),
+        }
+    }
+}
+
+/// The markers over a player's head, in the order they are drawn from left to right.
+#[derive(Component, Reflect, Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct StatusMarkers(pub Vec<StatusMarker>);
+
+impl StatusMarkers {
+    pub fn contains(&self, marker: &StatusMarker) -> bool { self.0.contains(marker) }
+
+    /// Adds the marker if it isn't already shown, or removes it if it is.
+    pub fn toggle(&mut self, marker: StatusMarker) {
+        if let Some(index) = self.0.iter().position(|m| *m == marker) {
+            self.0.remove(index);
+        } else {
+            self.0.push(marker);
+        }
+    }
+
+    /// Produces the player's limit cut number, if they have one.
+    pub fn limit_cut(&self) -> Option<u8> {
+        self.0.iter().find_map(|marker| match marker {
+            StatusMarker::LimitCut(n) => Some(*n),
+            _ => None,
+        })
+    }
+
+    /// Sets the player's limit cut number, replacing any they already have.
+    pub fn set_limit_cut(&mut self, n: Option<u8>) {
+        let index = self
+            .0
+            .iter()
+            .position(|marker| matches!(marker, StatusMarker::LimitCut(_)));
+        match (index, n) {
+            (Some(index), Some(n)) => self.0[index] = StatusMarker::LimitCut(n),
+            (Some(index), None) => {
+                self.0.remove(index);
+            }
+            (None, Some(n)) => self.0.push(StatusMarker::LimitCut(n)),
+            (None, None) => {}
+        }
+    }
+
+    /// Produces the position of each of `count` markers, relative to the center of the player.
+    ///
+    /// Markers are laid out in a row centered above the player.
+    pub fn layout(count: usize) -> impl Iterator<Item = Vec2> {
+        let step = MARKER_SIZE + MARKER_SEP;
+        let first = -(count.saturating_sub(1) as f32) * step / 2.0;
+        let y = (PLAYER_SPRITE_SIZE + MARKER_SIZE) / 2.0;
+        (0..count).map(move |i| Vec2::new(first + i as f32 * step, y))
+    }
+
+    /// [System] that redraws the marker icons of players whose markers have changed.
+    pub fn update_icons(
+        q: Query<(Entity, &StatusMarkers, Option<&Children>), Changed<StatusMarkers>>,
+        icon_q: Query<(), With<MarkerIcon>>,
+        mut commands: Commands,
+    ) {
+        for (id, markers, children) in &q {
+            for &child in children.into_iter().flatten() {
+                if icon_q.contains(child) {
+                    commands.entity(child).despawn_recursive();
+                }
+            }
+            commands.entity(id).with_children(|parent| {
+                for (marker, position) in markers.0.iter().zip(Self::layout(markers.0.len())) {
+                    marker.spawn_icon(parent, position);
+                }
+            });
+        }
+    }
+}
+
+/// Marker component for the icon of a [`StatusMarker`] over a player's head.
+#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
+pub struct MarkerIcon;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn toggle_markers() {
+        let mut markers = StatusMarkers::default();
+        markers.toggle(StatusMarker::Stack);
+        markers.toggle(StatusMarker::Debuff(DebuffColor::Blue));
+        markers.set_limit_cut(Some(3));
+        assert_eq!(markers.limit_cut(), Some(3));
+        markers.set_limit_cut(Some(5));
+        markers.toggle(StatusMarker::Stack);
+        assert_eq!(markers.0, vec![
+            StatusMarker::Debuff(DebuffColor::Blue),
+            StatusMarker::LimitCut(5)
+        ]);
+        markers.set_limit_cut(None);
+        assert_eq!(markers.limit_cut(), None);
+
+        assert_eq!(
+            StatusMarker::LimitCut(8).asset_path(),
+            Some("sprites/markers/limit_cut_8.png")
+        );
+        assert_eq!(StatusMarker::LimitCut(0).asset_path(), None);
+        assert_eq!(StatusMarker::LimitCut(9).asset_path(), None);
+        assert_eq!(StatusMarker::Text("Bait".into()).asset_path(), None);
+    }
+
+    #[test]
+    fn marker_layout() {
+        let one = StatusMarkers::layout(1).collect::<Vec<_>>();
+        assert_eq!(one.len(), 1);
+        assert_eq!(one[0].x, 0.0);
+        assert!(one[0].y > PLAYER_SPRITE_SIZE / 2.0);
+
+        let three = StatusMarkers::layout(3).collect::<Vec<_>>();
+        assert_eq!(three[1].x, 0.0);
+        assert_eq!(three[0].x, -three[2].x);
+        assert!(three[2].x - three[1].x >= MARKER_SIZE);
+    }
+}
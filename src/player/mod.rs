@@ -3,16 +3,34 @@ use bevy::{
     ecs::{component::ComponentId, world::DeferredWorld},
     prelude::*,
 };
-use job::Job;
+use facing::{Facing, FacingArrow};
+use job::{Job, Role};
+use marker::StatusMarkers;
+use party::{Party, PartySlot};
+use positional::PositionalUptime;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    aoe::HitBy,
     drag::Draggable,
     image::{DrawImage, DrawImageKind},
+    margin::Margin,
     spawner::Spawnable,
+    view::Upright,
 };
 
+pub mod facing;
 pub mod job;
+pub mod marker;
+pub mod party;
+pub mod positional;
 
+#[cfg(feature = "egui")]
+mod context_egui;
+pub mod context {
+    #[cfg(feature = "egui")]
+    pub use super::context_egui::*;
+}
 #[cfg(feature = "egui")]
 mod window_egui;
 pub mod window {
@@ -21,36 +39,58 @@ pub mod window {
 }
 
 /// The size of a player icon.
-const PLAYER_SPRITE_SIZE: f32 = 2.0;
+pub const PLAYER_SPRITE_SIZE: f32 = 2.0;
 const PLAYER_COLLIDER_SIZE: f32 = 0.001;
-const PLAYER_Z: f32 = 500.0;
+pub const PLAYER_Z: f32 = 500.0;
 
-#[derive(Copy, Clone, Hash, PartialEq, Eq, Ord, PartialOrd, Component, Reflect)]
+/// A player on the board.
+///
+/// Players are identified by their slot in the [`Party`], not by their job, so that a party can
+/// have two of the same job, and changing a slot's job changes the player's icon in place.
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Ord, PartialOrd, Debug)]
+#[derive(Component, Reflect)]
 #[require(Transform(|| Transform::from_xyz(0.0, 0.0, PLAYER_Z)))]
 #[require(Collider(|| Collider::circle(PLAYER_COLLIDER_SIZE)))]
-#[require(Draggable, PlayerSprite)]
+#[require(Draggable, PlayerSprite, Facing, StatusMarkers, Upright, HitBy, Margin)]
 #[component(on_add = Self::on_add)]
-pub struct Player {}
+pub struct Player {
+    pub slot: PartySlot,
+}
 
 impl Player {
     pub fn on_add(mut world: DeferredWorld, id: Entity, _: ComponentId) {
-        let sprite = *world.get::<PlayerSprite>(id).unwrap();
-        if let Some(job) = sprite.job {
-            let name = job.to_string();
-            world.commands().entity(id).insert_if_new(Name::new(name));
-        }
+        let slot = world.get::<Player>(id).unwrap().slot;
+        let sprite = world
+            .get_resource::<Party>()
+            .map_or_else(default, |party| party.sprite(slot));
+        *world.get_mut::<PlayerSprite>(id).unwrap() = sprite;
+
+        world
+            .commands()
+            .entity(id)
+            .insert_if_new(Name::new(slot.abbrev()));
         world.commands().entity(id).insert(DrawImage::new(
             sprite.asset_path().into(),
             Vec2::splat(PLAYER_SPRITE_SIZE),
             DrawImageKind::Sprite,
         ));
     }
+
+    /// [System] that updates the job of each player to match their slot in the [`Party`].
+    pub fn update_jobs(party: Res<Party>, mut q: Query<(&Player, &mut PlayerSprite)>) {
+        for (player, mut sprite) in &mut q {
+            sprite.set_if_neq(party.sprite(player.slot));
+        }
+    }
 }
 
 #[derive(Copy, Default, Clone, Hash, PartialEq, Eq, Ord, PartialOrd, Debug)]
-#[derive(Component, Reflect)]
+#[derive(Component, Reflect, Serialize, Deserialize)]
 pub struct PlayerSprite {
     pub job: Option<Job>,
+    /// The player's role, which is used for the icon if the job isn't known.
+    #[serde(default)]
+    pub role: Option<Role>,
 }
 
 impl PlayerSprite {
@@ -67,15 +107,52 @@ impl PlayerSprite {
         }
     }
 
+    /// Produces the player's role, from their job if it's known.
+    pub fn role(self) -> Option<Role> { self.job.map(Job::role).or(self.role) }
+
     pub fn asset_path(self) -> &'static str {
-        self.job
-            .map_or(Job::none_asset_path(), Job::icon_asset_path)
+        match (self.job, self.role) {
+            (Some(job), _) => job.icon_asset_path(),
+            (None, Some(role)) => role.icon_asset_path(),
+            (None, None) => Job::none_asset_path(),
+        }
     }
 }
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
-    fn build(&self, app: &mut App) { app.add_systems(PostUpdate, PlayerSprite::update_sprites); }
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Party>()
+            .register_type::<Party>()
+            .register_type::<Facing>()
+            .register_type::<StatusMarkers>()
+            .register_type::<PositionalUptime>()
+            .add_systems(
+                PostUpdate,
+                FacingArrow::update_rotations
+                    .after(Upright::update_rotations)
+                    .before(TransformSystem::TransformPropagate),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    Player::update_jobs.run_if(resource_changed::<Party>),
+                    PlayerSprite::update_sprites,
+                )
+                    .chain(),
+            )
+            .add_systems(PostUpdate, StatusMarkers::update_icons)
+            .add_systems(
+                PostUpdate,
+                PositionalUptime::update_uptimes.before(TransformSystem::TransformPropagate),
+            );
+        #[cfg(feature = "egui")]
+        app.add_systems(Update, Facing::scroll_while_dragged)
+            .add_systems(
+                PostUpdate,
+                PositionalUptime::update_labels.after(PositionalUptime::update_uptimes),
+            );
+    }
 }
 
 pub fn plugin() -> PlayerPlugin { PlayerPlugin }
@@ -0,0 +1,143 @@
+//! Party composition.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    job::{Job, Role},
+    PlayerSprite,
+};
+
+/// A slot in a standard light party or full party.
+#[derive(Copy, Clone, Debug, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Reflect, Serialize, Deserialize, Sequence)]
+pub enum PartySlot {
+    MT,
+    OT,
+    H1,
+    H2,
+    M1,
+    M2,
+    R1,
+    R2,
+}
+
+impl PartySlot {
+    /// Produces the usual abbreviation for the slot.
+    pub fn abbrev(self) -> &'static str {
+        match self {
+            PartySlot::MT => "MT",
+            PartySlot::OT => "OT",
+            PartySlot::H1 => "H1",
+            PartySlot::H2 => "H2",
+            PartySlot::M1 => "M1",
+            PartySlot::M2 => "M2",
+            PartySlot::R1 => "R1",
+            PartySlot::R2 => "R2",
+        }
+    }
+
+    /// Produces the role usually played by this slot.
+    pub fn role(self) -> Role {
+        match self {
+            PartySlot::MT | PartySlot::OT => Role::Tank,
+            PartySlot::H1 => Role::PureHealer,
+            PartySlot::H2 => Role::BarrierHealer,
+            PartySlot::M1 | PartySlot::M2 => Role::Melee,
+            PartySlot::R1 => Role::PhysicalRanged,
+            PartySlot::R2 => Role::Caster,
+        }
+    }
+}
+
+/// The composition of the party: the job in each [`PartySlot`].
+///
+/// A slot with no job is a generic player of the slot's [role](PartySlot::role),
+/// for when the comp isn't known.
+#[derive(Resource, Reflect, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Party {
+    slots: BTreeMap<PartySlot, Option<Job>>,
+}
+
+impl Default for Party {
+    fn default() -> Self {
+        Self::from_jobs([
+            Some(Job::Paladin),
+            Some(Job::DarkKnight),
+            Some(Job::Astrologian),
+            Some(Job::Scholar),
+            Some(Job::Dragoon),
+            Some(Job::RedMage),
+            Some(Job::Bard),
+            Some(Job::Pictomancer),
+        ])
+    }
+}
+
+impl Party {
+    /// Produces a party with the given jobs, in slot order.
+    pub fn from_jobs(jobs: [Option<Job>; 8]) -> Self {
+        Self {
+            slots: enum_iterator::all::<PartySlot>().zip(jobs).collect(),
+        }
+    }
+
+    /// Produces the job in the given slot, if there is one.
+    pub fn job(&self, slot: PartySlot) -> Option<Job> { self.slots.get(&slot).copied().flatten() }
+
+    pub fn set_job(&mut self, slot: PartySlot, job: Option<Job>) { self.slots.insert(slot, job); }
+
+    /// Produces the role of the player in the given slot.
+    pub fn role(&self, slot: PartySlot) -> Role { self.job(slot).map_or(slot.role(), Job::role) }
+
+    /// Produces the sprite for the player in the given slot.
+    pub fn sprite(&self, slot: PartySlot) -> PlayerSprite {
+        PlayerSprite {
+            job: self.job(slot),
+            role: Some(self.role(slot)),
+        }
+    }
+
+    /// Produces every slot and its job, in slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (PartySlot, Option<Job>)> + '_ {
+        enum_iterator::all::<PartySlot>().map(|slot| (slot, self.job(slot)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn party_round_trip() {
+        let mut party = Party::default();
+        party.set_job(PartySlot::M2, None);
+        party.set_job(PartySlot::R2, Some(Job::RedMage));
+
+        let ron = ron::to_string(&party).unwrap();
+        let parsed: Party = ron::from_str(&ron).unwrap();
+        assert_eq!(parsed, party);
+        assert_eq!(parsed.job(PartySlot::M2), None);
+        assert_eq!(parsed.job(PartySlot::R2), Some(Job::RedMage));
+        assert_eq!(parsed.iter().count(), 8);
+    }
+
+    #[test]
+    fn missing_slots_are_generic() {
+        let party: Party = ron::from_str("(slots: {MT: Some(Warrior)})").unwrap();
+        assert_eq!(party.job(PartySlot::MT), Some(Job::Warrior));
+        assert_eq!(party.job(PartySlot::OT), None);
+        assert_eq!(party.role(PartySlot::OT), Role::Tank);
+        assert_eq!(
+            party.sprite(PartySlot::OT).asset_path(),
+            Role::Tank.icon_asset_path()
+        );
+        assert_eq!(
+            party.iter().map(|(slot, _)| slot).collect::<Vec<_>>(),
+            enum_iterator::all::<PartySlot>().collect::<Vec<_>>()
+        );
+    }
+}
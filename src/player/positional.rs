@@ -0,0 +1,233 @@
+//! Positionals for melee players.
+//!
+//! Melee jobs have attacks that do more when used from an enemy's flank or rear. Each melee
+//! [`Player`] is checked against the nearest enemy [`Hitbox`], and labelled with which side of it
+//! they're on and whether they're in max melee.
+
+use std::f32::consts::FRAC_PI_4;
+
+use bevy::{
+    ecs::{component::ComponentId, world::DeferredWorld},
+    prelude::*,
+    transform::helper::TransformHelper,
+};
+
+use super::{facing::Facing, Player, PlayerSprite, PLAYER_SPRITE_SIZE};
+#[cfg(feature = "egui")]
+use crate::label::{text_label, SMALL_LABEL_FONT_SIZE};
+use crate::{ecs::TransformHelperExt, hitbox::Hitbox};
+
+/// The color of the label of a player who is in max melee.
+const IN_MELEE_COLOR: Color = Color::srgb(0.4, 0.9, 0.4);
+/// The color of the label of a player who is out of max melee.
+const OUT_OF_MELEE_COLOR: Color = Color::srgb(1.0, 0.35, 0.35);
+
+/// Which side of an enemy a player is on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum Positional {
+    Front,
+    Flank,
+    Rear,
+    /// The enemy has an omnidirectional hitbox, so every positional is hit.
+    Any,
+}
+
+impl Positional {
+    pub fn name(self) -> &'static str {
+        match self {
+            Positional::Front => "Front",
+            Positional::Flank => "Flank",
+            Positional::Rear => "Rear",
+            Positional::Any => "Any",
+        }
+    }
+}
+
+/// Where a player stands relative to an enemy, for the purposes of positionals.
+///
+/// Melee players are kept up to date with the nearest enemy; see [`positional_uptime`] to check
+/// any other position.
+#[derive(Component, Reflect, Copy, Clone, Debug, PartialEq)]
+#[component(on_add = Self::on_add)]
+pub struct PositionalUptime {
+    pub positional: Positional,
+    /// Whether the player is within max melee of the enemy.
+    pub in_max_melee: bool,
+}
+
+/// Produces the positional uptime that a player standing at `player` would have against an enemy
+/// at `enemy`.
+///
+/// The sides are divided by the diagonal lines drawn with the max melee range.
+pub fn positional_uptime(
+    enemy: Vec2,
+    facing: Facing,
+    hitbox: &Hitbox,
+    player: Vec2,
+) -> PositionalUptime {
+    let offset = player - enemy;
+    let positional = if hitbox.is_directional() {
+        let angle = facing.direction().angle_to(offset).abs();
+        if angle < FRAC_PI_4 {
+            Positional::Front
+        } else if angle > 3.0 * FRAC_PI_4 {
+            Positional::Rear
+        } else {
+            Positional::Flank
+        }
+    } else {
+        Positional::Any
+    };
+    PositionalUptime {
+        positional,
+        in_max_melee: offset.length() <= hitbox.max_melee_radius(),
+    }
+}
+
+impl PositionalUptime {
+    /// Returns true if the player can hit the given positional from here.
+    pub fn hits(self, positional: Positional) -> bool {
+        self.in_max_melee && (self.positional == positional || self.positional == Positional::Any)
+    }
+
+    /// Produces a short description, like "Rear" or "Flank (out of melee)".
+    pub fn label(self) -> String {
+        if self.in_max_melee {
+            self.positional.name().to_owned()
+        } else {
+            format!("{} (out of melee)", self.positional.name())
+        }
+    }
+
+    /// Adds the label.
+    fn on_add(mut world: DeferredWorld, id: Entity, _: ComponentId) {
+        #[cfg(feature = "egui")]
+        world.commands().entity(id).with_child((
+            Name::new("Positional Label"),
+            PositionalLabel,
+            text_label(
+                SMALL_LABEL_FONT_SIZE,
+                IN_MELEE_COLOR,
+                Vec2::new(0.0, -PLAYER_SPRITE_SIZE * 0.75),
+            ),
+        ));
+    }
+
+    /// [System] that checks each melee player against the nearest enemy, and stops checking
+    /// players who aren't melee or have no enemy to hit, removing their labels.
+    ///
+    /// Uses the latest [`Transform`]s, so that the check follows players while they are dragged.
+    #[allow(clippy::type_complexity)]
+    pub fn update_uptimes(
+        mut player_q: Query<
+            (
+                Entity,
+                &PlayerSprite,
+                Option<&mut PositionalUptime>,
+                Option<&Children>,
+            ),
+            With<Player>,
+        >,
+        enemy_q: Query<(Entity, &Hitbox, &Facing)>,
+        label_q: Query<(), With<PositionalLabel>>,
+        helper: TransformHelper,
+        mut commands: Commands,
+    ) {
+        let enemies = enemy_q
+            .iter()
+            .filter_map(|(id, hitbox, &facing)| {
+                Some((helper.world_position(id).ok()?, hitbox, facing))
+            })
+            .collect::<Vec<_>>();
+
+        for (id, sprite, uptime, children) in &mut player_q {
+            let melee = sprite.role().is_some_and(|role| role.has_positionals());
+            let nearest = helper
+                .world_position(id)
+                .ok()
+                .filter(|_| melee)
+                .and_then(|player| {
+                    enemies
+                        .iter()
+                        .map(|&(enemy, hitbox, facing)| {
+                            let gap = player.distance(enemy) - hitbox.outer_radius;
+                            (gap, positional_uptime(enemy, facing, hitbox, player))
+                        })
+                        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+                });
+            match (nearest, uptime) {
+                (Some((_, new)), Some(mut uptime)) => {
+                    uptime.set_if_neq(new);
+                }
+                (Some((_, new)), None) => {
+                    commands.entity(id).insert(new);
+                }
+                (None, Some(_)) => {
+                    commands.entity(id).remove::<PositionalUptime>();
+                    for &child in children.into_iter().flatten() {
+                        if label_q.contains(child) {
+                            commands.entity(child).despawn_recursive();
+                        }
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+    }
+
+    /// [System] that updates the labels of players whose positional uptime has changed.
+    #[cfg(feature = "egui")]
+    pub fn update_labels(
+        q: Query<(&PositionalUptime, &Children), Changed<PositionalUptime>>,
+        mut label_q: Query<(&mut Text2d, &mut TextColor), With<PositionalLabel>>,
+    ) {
+        for (uptime, children) in &q {
+            let mut labels = label_q.iter_many_mut(children);
+            while let Some((mut text, mut color)) = labels.fetch_next() {
+                text.0 = uptime.label();
+                color.0 = if uptime.in_max_melee {
+                    IN_MELEE_COLOR
+                } else {
+                    OUT_OF_MELEE_COLOR
+                };
+            }
+        }
+    }
+}
+
+/// Marker component for the label showing a player's [`PositionalUptime`].
+#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
+pub struct PositionalLabel;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hitbox::HitboxKind;
+
+    #[test]
+    fn positionals() {
+        let hitbox = Hitbox::new(HitboxKind::Directional, Color::WHITE, 5.0);
+        let north = Facing::new(0.0);
+        let check = |player| positional_uptime(Vec2::ZERO, north, &hitbox, player);
+
+        assert_eq!(check(Vec2::new(0.0, 6.0)).positional, Positional::Front);
+        assert_eq!(check(Vec2::new(-6.0, 1.0)).positional, Positional::Flank);
+        assert_eq!(check(Vec2::new(1.0, -6.0)).positional, Positional::Rear);
+        assert!(check(Vec2::new(1.0, -6.0)).hits(Positional::Rear));
+        assert!(!check(Vec2::new(1.0, -6.0)).hits(Positional::Flank));
+
+        let far = check(Vec2::new(0.0, -8.5));
+        assert_eq!(far.positional, Positional::Rear);
+        assert!(!far.in_max_melee);
+        assert!(!far.hits(Positional::Rear));
+
+        // Turning the enemy turns its sides.
+        let east = positional_uptime(Vec2::ZERO, Facing::new(90.0), &hitbox, Vec2::new(-6.0, 0.0));
+        assert_eq!(east.positional, Positional::Rear);
+
+        let omni = Hitbox::new(HitboxKind::Omni, Color::WHITE, 5.0);
+        let any = positional_uptime(Vec2::ZERO, north, &omni, Vec2::new(0.0, 6.0));
+        assert_eq!(any.positional, Positional::Any);
+        assert!(any.hits(Positional::Flank));
+    }
+}
@@ -7,7 +7,7 @@ use bevy::{
 use bevy_egui::egui;
 use itertools::Itertools;
 
-use super::{job::Job, Player, PlayerSprite, PLAYER_Z};
+use super::{job::Job, party::Party, Player, PLAYER_Z};
 use crate::{
     ecs::{EntityWorldExts, NestedSystemExts},
     ui::widget::{egui_context, Widget, WidgetSystemId},
@@ -17,16 +17,19 @@ use crate::{
 const SIZE: f32 = 35.0;
 const SEP: f32 = 10.0;
 
-impl Spawnable for PlayerSprite {
+impl Spawnable for Player {
     const UNIQUE: bool = true;
     const Z: f32 = PLAYER_Z;
 
+    type SortKey = Self;
+
     fn size() -> Vec2 { Vec2::splat(SIZE) }
     fn sep() -> Vec2 { Vec2::splat(SEP) }
 
-    fn spawner_name(&self) -> std::borrow::Cow<'static, str> { format!("{:#?}", self.job).into() }
+    fn spawner_name(&self) -> std::borrow::Cow<'static, str> { self.slot.abbrev().into() }
+    fn sort_key(&self) -> Self { *self }
 
-    fn insert(&self, entity: &mut EntityCommands) { entity.insert((Player {}, *self)); }
+    fn insert(&self, entity: &mut EntityCommands) { entity.insert(*self); }
 }
 
 /// A window with controls to manipulate the waymarks.
@@ -42,12 +45,12 @@ impl PlayerWindow {
         let ctx = egui_context(world);
         let mut state = SystemState::<(
             Query<Entity, With<PlayerWindow>>,
-            Query<&Widget, With<SpawnerPanel<PlayerSprite>>>,
+            Query<&Widget, With<SpawnerPanel<Player>>>,
             Query<&Children>,
         )>::new(world);
 
-        let ewin = egui::Window::new("Players")
-            .default_width(4.0 * (PlayerSprite::size() + PlayerSprite::sep()).x);
+        let ewin =
+            egui::Window::new("Players").default_width(4.0 * (Player::size() + Player::sep()).x);
         ewin.show(&ctx, |ui| {
             let (mut win_q, panel_q, parent_q) = state.get_mut(world);
             let win_id = win_q.single_mut();
@@ -60,38 +63,69 @@ impl PlayerWindow {
             panel.show_world(world, ui);
 
             state.apply(world);
+
+            ui.collapsing("Party", |ui| {
+                Self::edit_party(&mut world.resource_mut::<Party>(), ui);
+            });
         });
     }
 
-    /// Setup the window.
-    pub fn on_add(mut world: DeferredWorld, id: Entity, _: ComponentId) {
-        const JOBS: [Job; 8] = [
-            Job::Paladin,
-            Job::DarkKnight,
-            Job::Astrologian,
-            Job::Scholar,
-            Job::RedMage,
-            Job::Bard,
-            Job::Pictomancer,
-            Job::Dragoon,
-        ];
-
-        world.commands().queue(move |world: &mut World| {
-            world.entity_mut(id).with_children(move |window| {
-                window
-                    .spawn(SpawnerPanel::<PlayerSprite>::new())
-                    .with_children(move |panel| {
-                        for job in JOBS {
-                            let sprite = PlayerSprite { job: Some(job) };
-                            panel.spawn(Spawner::<PlayerSprite>::new(
-                                sprite,
-                                sprite.asset_path().into(),
-                            ));
+    /// Draws the controls to change the job in each party slot.
+    fn edit_party(party: &mut Mut<Party>, ui: &mut egui::Ui) {
+        egui::Grid::new("Party").show(ui, |ui| {
+            for (slot, job) in party.iter().collect_vec() {
+                ui.label(slot.abbrev());
+                let mut selected = job;
+                egui::ComboBox::from_id_salt(slot)
+                    .selected_text(job.map_or("Any", Job::abbrev))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut selected,
+                            None,
+                            format!("Any {}", slot.role().name()),
+                        );
+                        for job in enum_iterator::all::<Job>() {
+                            ui.selectable_value(&mut selected, Some(job), job.abbrev());
                         }
                     });
-            });
+                if selected != job {
+                    party.set_job(slot, selected);
+                }
+                ui.end_row();
+            }
         });
     }
+
+    /// Setup the window.
+    ///
+    /// The spawners themselves are added by [`PlayerWindow::update_spawners`].
+    pub fn on_add(mut world: DeferredWorld, id: Entity, _: ComponentId) {
+        world
+            .commands()
+            .entity(id)
+            .with_child(SpawnerPanel::<Player>::new());
+    }
+
+    /// [System] that rebuilds the player spawners from the [`Party`] whenever it changes.
+    pub fn update_spawners(
+        party: Res<Party>,
+        panel_q: Query<Entity, With<SpawnerPanel<Player>>>,
+        mut commands: Commands,
+    ) {
+        for panel in &panel_q {
+            commands
+                .entity(panel)
+                .despawn_descendants()
+                .with_children(|panel| {
+                    for (slot, _) in party.iter() {
+                        panel.spawn(Spawner::new(
+                            Player { slot },
+                            party.sprite(slot).asset_path().into(),
+                        ));
+                    }
+                });
+        }
+    }
 }
 
 /// Plugin for the waymark window.
@@ -100,8 +134,12 @@ pub struct WaymarkWindowPlugin;
 
 impl Plugin for WaymarkWindowPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(spawner::plugin::<PlayerSprite>())
+        app.add_plugins(spawner::plugin::<Player>())
             .add_systems(Update, PlayerWindow::show)
+            .add_systems(
+                PostUpdate,
+                PlayerWindow::update_spawners.run_if(resource_changed::<Party>),
+            )
             .add_systems(Startup, |mut commands: Commands| {
                 commands.spawn((PlayerWindow, Name::new("Players")));
             });
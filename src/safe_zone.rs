@@ -0,0 +1,137 @@
+//! Safe zones: the parts of each arena that no AoE covers.
+//!
+//! Whenever an AoE or arena moves or changes, every arena's shape has the union of all AoEs cut out
+//! of it, and what's left is drawn as a highlighted [`SafeZone`] over the arena.
+
+use bevy::prelude::*;
+#[cfg(feature = "egui")]
+use bevy::transform::helper::TransformHelper;
+use geo::{BooleanOps, MultiPolygon};
+
+#[cfg(feature = "egui")]
+use crate::{
+    aoe::Aoe,
+    arena::Arena,
+    shape::{build_mesh, triangulate, Shape},
+};
+
+/// The Z-coordinate of safe zones. They are drawn over the arena, but under its overlays.
+#[cfg(feature = "egui")]
+const SAFE_ZONE_Z: f32 = 0.5;
+/// The color of safe zones.
+#[cfg(feature = "egui")]
+const SAFE_ZONE_COLOR: Color = Color::srgba(0.3, 1.0, 0.5, 0.3);
+
+/// Marker component for the drawing of the safe zone on an arena.
+#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
+pub struct SafeZone;
+
+/// Produces the parts of `arena` that aren't covered by any of `aoes`.
+pub fn safe_region(arena: &MultiPolygon<f32>, aoes: &[MultiPolygon<f32>]) -> MultiPolygon<f32> {
+    match aoes.iter().cloned().reduce(|union, aoe| union.union(&aoe)) {
+        Some(union) => arena.difference(&union),
+        None => arena.clone(),
+    }
+}
+
+impl SafeZone {
+    /// [System] that redraws the safe zones when any AoE or arena has moved, changed, or gone.
+    ///
+    /// Nothing is drawn while there are no AoEs, since the whole arena is safe.
+    #[cfg(feature = "egui")]
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
+    pub fn update_zones(
+        arena_q: Query<(Entity, &Shape), With<Arena>>,
+        aoe_q: Query<(Entity, &Shape), With<Aoe>>,
+        changed_q: Query<
+            (),
+            (
+                Or<(With<Arena>, With<Aoe>)>,
+                Or<(Changed<Shape>, Changed<Transform>)>,
+            ),
+        >,
+        mut removed_arenas: RemovedComponents<Arena>,
+        mut removed_aoes: RemovedComponents<Aoe>,
+        zone_q: Query<Entity, With<SafeZone>>,
+        helper: TransformHelper,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        mut commands: Commands,
+    ) {
+        let removed = removed_arenas.read().count() + removed_aoes.read().count() > 0;
+        if changed_q.is_empty() && !removed {
+            return;
+        }
+        for id in &zone_q {
+            commands.entity(id).despawn_recursive();
+        }
+
+        let to_world = |(id, shape): (Entity, &Shape)| {
+            let transform = helper.compute_global_transform(id).ok()?;
+            Some(shape.to_world_polygons(&transform))
+        };
+        let aoes = aoe_q.iter().filter_map(to_world).collect::<Vec<_>>();
+        if aoes.is_empty() {
+            return;
+        }
+        for arena in arena_q.iter().filter_map(to_world) {
+            let (vertices, indices) = triangulate(&safe_region(&arena, &aoes));
+            commands.spawn((
+                Name::new("Safe Zone"),
+                SafeZone,
+                Mesh2d(meshes.add(build_mesh(vertices, indices))),
+                MeshMaterial2d(materials.add(SAFE_ZONE_COLOR)),
+                Transform::from_xyz(0.0, 0.0, SAFE_ZONE_Z),
+            ));
+        }
+    }
+}
+
+/// Plugin for safe zones.
+#[derive(Default, Copy, Clone, Debug)]
+pub struct SafeZonePlugin;
+
+impl Plugin for SafeZonePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SafeZone>();
+        #[cfg(feature = "egui")]
+        app.add_systems(
+            PostUpdate,
+            SafeZone::update_zones
+                .after(crate::shape::ShapeAnchor::follow_targets)
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+pub fn plugin() -> SafeZonePlugin { SafeZonePlugin }
+
+#[cfg(test)]
+mod test {
+    use geo::{Area, Contains};
+
+    use super::*;
+    use crate::shape::Shape;
+
+    #[test]
+    fn safe_regions() {
+        let arena = Shape::Rectangle(Rectangle::from_length(20.0)).to_polygons();
+        let circle = Shape::Circle(Circle::new(5.0)).to_polygons();
+        // A strip down the east side of the arena, hanging off its edge.
+        let strip = Shape::Rectangle(Rectangle::new(6.0, 30.0))
+            .to_world_polygons(&GlobalTransform::from_xyz(9.0, 0.0, 0.0));
+
+        assert_eq!(safe_region(&arena, &[]), arena);
+
+        let safe = safe_region(&arena, &[circle, strip]);
+        let expected = 400.0 - 25.0 * std::f32::consts::PI - 20.0 * 4.0;
+        assert!(
+            (safe.unsigned_area() - expected).abs() < 0.5,
+            "{}",
+            safe.unsigned_area()
+        );
+        assert!(safe.contains(&geo::coord! { x: -8.0, y: 8.0 }));
+        assert!(!safe.contains(&geo::coord! { x: 0.0, y: 0.0 }));
+        assert!(!safe.contains(&geo::coord! { x: 8.0, y: -8.0 }));
+    }
+}
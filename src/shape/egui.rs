@@ -1,8 +1,15 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    render::mesh::{Indices, PrimitiveTopology},
+};
 use bevy_vector_shapes::prelude::*;
 use itertools::Itertools;
 
 use super::*;
 
+/// The maximum length of a miter joint in a polygon stroke, relative to the stroke thickness.
+const MITER_LIMIT: f32 = 4.0;
+
 #[derive(Copy, Clone, Debug, Default, Component)]
 #[derive(Reflect, Serialize, Deserialize)]
 #[require(AlphaScale, Transform(|| Transform::from_xyz(0.0, 0.0, -0.1)), Visibility)]
@@ -13,25 +20,44 @@ pub struct ShapeFill;
 pub struct ShapeStroke;
 
 type AllBvsComps = (ShapeMaterial, ShapeFill, DiscComponent, RectangleComponent);
+type AllMeshComps = (Mesh2d, MeshMaterial2d<ColorMaterial>);
 
 impl DrawShape {
+    /// Updates the drawing of shapes.
+    ///
+    /// Primitive shapes are drawn with `bevy_vector_shapes`.
+    /// Everything else is converted to polygons and drawn as meshes.
     pub fn update_vector_shapes(
         q: Query<(&Shape, &DrawShape, &Children), Or<(Changed<Shape>, Changed<DrawShape>)>>,
         fill_q: Query<Entity, With<ShapeFill>>,
         stroke_q: Query<Entity, With<ShapeStroke>>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
         mut commands: Commands,
     ) {
         for (shape, draw, children) in &q {
             let bvs_material = ShapeMaterial::default();
+            let polygons = (!shape.is_primitive()).then(|| shape.to_polygons());
 
             let fill_id = fill_q.iter_many(children.iter()).exactly_one().unwrap();
             let mut fill_entity = commands.entity(fill_id);
-            if let Some(color) = draw.fill {
+            if let (Some(color), Some(polygons)) = (draw.fill, &polygons) {
+                let (vertices, indices) = triangulate(polygons);
+                fill_entity.remove::<AllBvsComps>().insert((
+                    Mesh2d(meshes.add(build_mesh(vertices, indices))),
+                    MeshMaterial2d(materials.add(color)),
+                    AlphaScale(color.alpha()),
+                ));
+            } else if let Some(color) = draw.fill {
                 let bvs_fill = bevy_vector_shapes::shapes::ShapeFill {
                     color,
                     ty: FillType::Fill,
                 };
-                fill_entity.insert((bvs_material.clone(), bvs_fill, AlphaScale(color.alpha())));
+                fill_entity.remove::<AllMeshComps>().insert((
+                    bvs_material.clone(),
+                    bvs_fill,
+                    AlphaScale(color.alpha()),
+                ));
                 match shape {
                     Shape::Circle(Circle { radius }) => {
                         fill_entity.insert(DiscComponent {
@@ -45,19 +71,31 @@ impl DrawShape {
                             ..default()
                         });
                     }
+                    _ => unreachable!("non-primitive shapes are drawn as meshes"),
                 }
             } else {
-                fill_entity.remove::<AllBvsComps>();
+                fill_entity.remove::<(AllBvsComps, AllMeshComps)>();
             }
 
             let stroke_id = stroke_q.iter_many(children.iter()).exactly_one().unwrap();
             let mut stroke_entity = commands.entity(stroke_id);
-            if let Some(stroke) = draw.stroke {
+            if let (Some(stroke), Some(polygons)) = (draw.stroke, &polygons) {
+                let (vertices, indices) = outline(polygons, stroke.thickness);
+                stroke_entity.remove::<AllBvsComps>().insert((
+                    Mesh2d(meshes.add(build_mesh(vertices, indices))),
+                    MeshMaterial2d(materials.add(stroke.color)),
+                    AlphaScale(stroke.color.alpha()),
+                ));
+            } else if let Some(stroke) = draw.stroke {
                 let bvs_fill = bevy_vector_shapes::shapes::ShapeFill {
                     color: stroke.color,
                     ty: FillType::Stroke(stroke.thickness, ThicknessType::World),
                 };
-                stroke_entity.insert((bvs_material, bvs_fill, AlphaScale(stroke.color.alpha())));
+                stroke_entity.remove::<AllMeshComps>().insert((
+                    bvs_material,
+                    bvs_fill,
+                    AlphaScale(stroke.color.alpha()),
+                ));
                 match shape {
                     Shape::Circle(Circle { radius }) => {
                         stroke_entity.insert(DiscComponent {
@@ -71,10 +109,61 @@ impl DrawShape {
                             ..default()
                         });
                     }
+                    _ => unreachable!("non-primitive shapes are drawn as meshes"),
                 }
             } else {
-                stroke_entity.remove::<AllBvsComps>();
+                stroke_entity.remove::<(AllBvsComps, AllMeshComps)>();
             }
         }
     }
 }
+
+/// Builds a flat 2D mesh out of triangles.
+pub fn build_mesh(vertices: Vec<Vec2>, indices: Vec<[u32; 3]>) -> Mesh {
+    let len = vertices.len();
+    let positions = vertices.into_iter().map(|v| v.extend(0.0)).collect_vec();
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![Vec3::Z; len])
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, vec![Vec2::ZERO; len])
+    .with_inserted_indices(Indices::U32(indices.into_flattened()))
+}
+
+/// Produces triangles covering a band `thickness` wide, centered on every ring of the polygons.
+fn outline(polygons: &MultiPolygon<f32>, thickness: f32) -> (Vec<Vec2>, Vec<[u32; 3]>) {
+    let half = thickness / 2.0;
+    let mut vertices = vec![];
+    let mut indices = vec![];
+    let rings = polygons
+        .iter()
+        .flat_map(|polygon| std::iter::once(polygon.exterior()).chain(polygon.interiors()));
+    for ring in rings {
+        // Rings are closed, so the last point is a repeat of the first.
+        let points = ring.coords().copied().map(to_vec2).collect_vec();
+        let Some((_, points)) = points.split_last() else {
+            continue;
+        };
+        let len = points.len();
+        let base = vertices.len() as u32;
+        for (i, &point) in points.iter().enumerate() {
+            let prev = points[(i + len - 1) % len];
+            let next = points[(i + 1) % len];
+            let in_normal = (point - prev).normalize_or_zero().perp();
+            let out_normal = (next - point).normalize_or_zero().perp();
+            let miter = (in_normal + out_normal)
+                .try_normalize()
+                .unwrap_or(in_normal);
+            let scale = (half / miter.dot(in_normal).max(f32::EPSILON)).min(half * MITER_LIMIT);
+            vertices.extend([point + miter * scale, point - miter * scale]);
+        }
+        for i in 0..len as u32 {
+            let j = (i + 1) % len as u32;
+            let [a, b, c, d] = [2 * i, 2 * i + 1, 2 * j, 2 * j + 1].map(|v| base + v);
+            indices.extend([[a, b, c], [c, b, d]]);
+        }
+    }
+    (vertices, indices)
+}
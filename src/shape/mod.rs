@@ -1,27 +1,389 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
 use avian2d::prelude::{Collider, PhysicsSet};
-use bevy::prelude::*;
+use bevy::{prelude::*, transform::helper::TransformHelper};
+use geo::{
+    BooleanOps, Contains, Distance, Euclidean, LineString, MapCoordsInPlace, MultiPolygon,
+    TriangulateEarcut,
+};
 use i_cant_believe_its_not_bsn::WithChild;
 use serde::{Deserialize, Serialize};
 
-use crate::color::AlphaScale;
+use crate::{color::AlphaScale, ecs::TransformHelperExt, player::facing::Facing};
 
 #[cfg(feature = "egui")]
 mod egui;
 #[cfg(feature = "egui")]
 pub use egui::*;
 
-#[derive(Copy, Clone, Debug, Component, Reflect, Serialize, Deserialize)]
+/// The number of segments used to approximate a full circle when a shape is converted to polygons.
+const CIRCLE_SEGMENTS: usize = 64;
+
+/// A 2D shape, positioned relative to its origin.
+///
+/// Most shapes are centered on the origin. The shapes of AoEs are instead anchored where they
+/// come from, and point towards +Y, so that they can be placed on and turned with whoever casts
+/// them.
+///
+/// In RON, the variants are distinguished by their fields, so the variant name is optional:
+///
+/// ```ron
+/// Circle(radius: 20.0)
+/// Rectangle(half_size: Vec2(20.0, 15.0))
+/// Annulus(inner_circle: (radius: 8.0), outer_circle: (radius: 20.0))
+/// Polygon(vertices: [Vec2(0.0, 10.0), Vec2(-10.0, -10.0), Vec2(10.0, -10.0)])
+/// Compound(
+///     union: [(shape: Circle(radius: 5.0), offset: Vec2(-10.0, 0.0))],
+///     difference: [(shape: Rectangle(half_size: Vec2(1.0, 1.0)), rotation: 45.0)],
+/// )
+/// Cone(radius: 40.0, angle: 90.0)
+/// Line(length: 40.0, width: 10.0)
+/// Cross(arm_length: 40.0, width: 8.0)
+/// HalfRoom(extent: 40.0)
+/// ```
+///
+/// Every variant rejects fields it doesn't know, so that a misspelled field is an error rather
+/// than silently producing a different shape.
+///
+/// An [`Annulus`] is the shape of a donut AoE.
+#[derive(Clone, Debug, PartialEq, Component, Reflect, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Shape {
+    #[serde(deserialize_with = "strict::circle")]
     Circle(Circle),
+    #[serde(deserialize_with = "strict::rectangle")]
     Rectangle(Rectangle),
+    #[serde(deserialize_with = "strict::annulus")]
+    Annulus(Annulus),
+    Polygon(Polygon),
+    Compound(Compound),
+    Cone(Cone),
+    Line(Line),
+    Cross(Cross),
+    HalfRoom(HalfRoom),
+}
+
+/// A cone with its point at the origin, opening towards +Y.
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Cone {
+    pub radius: f32,
+    /// The angle between the edges of the cone, in degrees.
+    pub angle: f32,
+}
+
+/// A line from the origin towards +Y, such as a line cleave.
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Line {
+    pub length: f32,
+    pub width: f32,
+}
+
+/// Two lines crossing at the origin, one along each axis.
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Cross {
+    /// The length of each arm, from the origin to its end.
+    pub arm_length: f32,
+    pub width: f32,
+}
+
+/// Everything on the +Y side of the origin, such as a cleave over half of the arena.
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HalfRoom {
+    /// How far the shape reaches, forwards and to either side.
+    ///
+    /// This should be large enough to cover the arena.
+    pub extent: f32,
+}
+
+/// An arbitrary simple polygon.
+#[derive(Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Polygon {
+    /// The vertices of the polygon, in order. The polygon is closed automatically.
+    pub vertices: Vec<Vec2>,
+}
+
+/// A shape made up of other shapes: the union of the `union` parts, minus the `difference` parts.
+#[derive(Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(no_field_bounds)]
+#[serde(deny_unknown_fields)]
+pub struct Compound {
+    pub union: Vec<ShapePart>,
+    #[serde(default)]
+    pub difference: Vec<ShapePart>,
+}
+
+/// A part of a [`Compound`] shape, positioned relative to the compound's origin.
+#[derive(Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(no_field_bounds)]
+#[serde(deny_unknown_fields)]
+pub struct ShapePart {
+    pub shape: Shape,
+    #[serde(default)]
+    pub offset: Vec2,
+    /// The counterclockwise rotation of the part around its own origin, in degrees.
+    #[serde(default)]
+    pub rotation: f32,
+}
+
+impl ShapePart {
+    /// Transforms a point from the compound's coordinates into the part's coordinates.
+    fn to_local(&self, point: Vec2) -> Vec2 {
+        Rot2::degrees(-self.rotation) * (point - self.offset)
+    }
+
+    /// Transforms a point from the part's coordinates into the compound's coordinates.
+    fn to_compound(&self, point: Vec2) -> Vec2 {
+        Rot2::degrees(self.rotation) * point + self.offset
+    }
+}
+
+/// Deserializers for the Bevy primitives in [`Shape`] that reject unknown fields, which the
+/// primitives themselves don't. Without them, a cone with a misspelled angle would be read as a
+/// circle.
+mod strict {
+    use bevy::prelude::*;
+    use serde::{Deserialize, Deserializer};
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct StrictCircle {
+        radius: f32,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct StrictRectangle {
+        half_size: Vec2,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct StrictAnnulus {
+        inner_circle: StrictCircle,
+        outer_circle: StrictCircle,
+    }
+
+    pub fn circle<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Circle, D::Error> {
+        let StrictCircle { radius } = StrictCircle::deserialize(deserializer)?;
+        Ok(Circle::new(radius))
+    }
+
+    pub fn rectangle<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rectangle, D::Error> {
+        let StrictRectangle { half_size } = StrictRectangle::deserialize(deserializer)?;
+        Ok(Rectangle { half_size })
+    }
+
+    pub fn annulus<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Annulus, D::Error> {
+        let StrictAnnulus {
+            inner_circle,
+            outer_circle,
+        } = StrictAnnulus::deserialize(deserializer)?;
+        Ok(Annulus::new(inner_circle.radius, outer_circle.radius))
+    }
+}
+
+impl Shape {
+    /// Produces true if `point`, relative to the origin of the shape, is inside the shape.
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            Shape::Cone(cone) => {
+                point.length_squared() <= cone.radius.powi(2)
+                    && Vec2::Y.angle_to(point).abs() <= cone.angle.to_radians() / 2.0
+            }
+            Shape::Circle(circle) => point.length_squared() <= circle.radius.powi(2),
+            Shape::Rectangle(rect) => point.abs().cmple(rect.half_size).all(),
+            Shape::Annulus(annulus) => {
+                let dist = point.length_squared();
+                dist >= annulus.inner_circle.radius.powi(2)
+                    && dist <= annulus.outer_circle.radius.powi(2)
+            }
+            Shape::Polygon(_) => self.to_polygons().contains(&to_coord(point)),
+            Shape::Compound(compound) => {
+                let in_any = |parts: &[ShapePart]| {
+                    parts
+                        .iter()
+                        .any(|part| part.shape.contains(part.to_local(point)))
+                };
+                in_any(&compound.union) && !in_any(&compound.difference)
+            }
+            Shape::Line(line) => {
+                point.x.abs() <= line.width / 2.0 && (0.0..=line.length).contains(&point.y)
+            }
+            Shape::Cross(cross) => {
+                let along =
+                    |a: f32, b: f32| a.abs() <= cross.arm_length && b.abs() <= cross.width / 2.0;
+                along(point.x, point.y) || along(point.y, point.x)
+            }
+            Shape::HalfRoom(half) => {
+                point.x.abs() <= half.extent && (0.0..=half.extent).contains(&point.y)
+            }
+        }
+    }
+
+    /// Converts the shape into polygons like [`Shape::to_polygons`], placed in world coordinates by
+    /// the transform of the shape's entity.
+    pub fn to_world_polygons(&self, transform: &GlobalTransform) -> MultiPolygon<f32> {
+        let mut polygons = self.to_polygons();
+        polygons.map_coords_in_place(|c| {
+            to_coord(transform.transform_point(to_vec2(c).extend(0.0)).truncate())
+        });
+        polygons
+    }
+
+    /// Produces the distance from `point`, relative to the origin of the shape, to the nearest edge
+    /// of the shape, whether the point is inside it or not.
+    ///
+    /// Circles are measured exactly; other curves are measured against their polygons.
+    pub fn edge_distance(&self, point: Vec2) -> f32 {
+        match self {
+            Shape::Circle(circle) => (point.length() - circle.radius).abs(),
+            Shape::Annulus(annulus) => {
+                let dist = point.length();
+                (dist - annulus.inner_circle.radius)
+                    .abs()
+                    .min((dist - annulus.outer_circle.radius).abs())
+            }
+            _ => {
+                let point = geo::Point::from(to_coord(point));
+                self.to_polygons()
+                    .iter()
+                    .flat_map(|polygon| {
+                        std::iter::once(polygon.exterior()).chain(polygon.interiors())
+                    })
+                    .map(|ring| Euclidean::distance(&point, ring))
+                    .fold(f32::INFINITY, f32::min)
+            }
+        }
+    }
+
+    /// Produces true if this shape can be drawn and collided with as a primitive,
+    /// rather than needing to be converted to polygons.
+    pub fn is_primitive(&self) -> bool { matches!(self, Shape::Circle(_) | Shape::Rectangle(_)) }
+
+    /// Converts the shape into polygons, approximating any curves.
+    pub fn to_polygons(&self) -> MultiPolygon<f32> {
+        match self {
+            Shape::Cone(cone) if cone.angle >= 360.0 => {
+                geo::Polygon::new(circle_ring(cone.radius), vec![]).into()
+            }
+            Shape::Cone(cone) => {
+                let angle = cone.angle.to_radians();
+                let segments = ((CIRCLE_SEGMENTS as f32 * angle / TAU).ceil() as usize).max(1);
+                let arc = (0..=segments).map(|i| {
+                    let theta = FRAC_PI_2 - angle / 2.0 + angle * i as f32 / segments as f32;
+                    to_coord(Vec2::from_angle(theta) * cone.radius)
+                });
+                let ring = std::iter::once(to_coord(Vec2::ZERO)).chain(arc).collect();
+                geo::Polygon::new(ring, vec![]).into()
+            }
+            Shape::Circle(circle) => geo::Polygon::new(circle_ring(circle.radius), vec![]).into(),
+            Shape::Rectangle(rect) => {
+                let Vec2 { x, y } = rect.half_size;
+                rect_polygon(-x, -y, x, y).into()
+            }
+            Shape::Annulus(annulus) => geo::Polygon::new(
+                circle_ring(annulus.outer_circle.radius),
+                vec![circle_ring(annulus.inner_circle.radius)],
+            )
+            .into(),
+            Shape::Polygon(polygon) => geo::Polygon::new(
+                polygon.vertices.iter().copied().map(to_coord).collect(),
+                vec![],
+            )
+            .into(),
+            Shape::Compound(compound) => {
+                let union = union_parts(&compound.union);
+                if compound.difference.is_empty() {
+                    union
+                } else {
+                    union.difference(&union_parts(&compound.difference))
+                }
+            }
+            Shape::Line(line) => {
+                let x = line.width / 2.0;
+                rect_polygon(-x, 0.0, x, line.length).into()
+            }
+            Shape::Cross(cross) => {
+                let (a, w) = (cross.arm_length, cross.width / 2.0);
+                rect_polygon(-w, -a, w, a).union(&rect_polygon(-a, -w, a, w))
+            }
+            Shape::HalfRoom(half) => {
+                rect_polygon(-half.extent, 0.0, half.extent, half.extent).into()
+            }
+        }
+    }
 }
 
-impl From<Shape> for Collider {
-    fn from(value: Shape) -> Self {
+/// Produces an axis-aligned rectangle from its corners.
+fn rect_polygon(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> geo::Polygon<f32> {
+    let ring = [
+        (min_x, min_y),
+        (max_x, min_y),
+        (max_x, max_y),
+        (min_x, max_y),
+    ];
+    geo::Polygon::new(LineString::from(ring.to_vec()), vec![])
+}
+
+fn to_coord(point: Vec2) -> geo::Coord<f32> { point.to_array().into() }
+
+fn to_vec2(coord: geo::Coord<f32>) -> Vec2 { Vec2::new(coord.x, coord.y) }
+
+/// Produces a closed ring approximating a circle.
+fn circle_ring(radius: f32) -> LineString<f32> {
+    (0..CIRCLE_SEGMENTS)
+        .map(|i| to_coord(Vec2::from_angle(TAU * i as f32 / CIRCLE_SEGMENTS as f32) * radius))
+        .collect()
+}
+
+/// Produces the union of the polygons of all the parts, in the compound's coordinates.
+fn union_parts(parts: &[ShapePart]) -> MultiPolygon<f32> {
+    parts
+        .iter()
+        .map(|part| {
+            let mut polygons = part.shape.to_polygons();
+            polygons.map_coords_in_place(|c| to_coord(part.to_compound(to_vec2(c))));
+            polygons
+        })
+        .reduce(|acc, polygons| acc.union(&polygons))
+        .unwrap_or_else(|| MultiPolygon::new(vec![]))
+}
+
+/// Triangulates polygons, producing the vertices and the triangles' indices into them.
+pub fn triangulate(polygons: &MultiPolygon<f32>) -> (Vec<Vec2>, Vec<[u32; 3]>) {
+    let mut vertices = vec![];
+    let mut indices = vec![];
+    for polygon in polygons {
+        let raw = polygon.earcut_triangles_raw();
+        let base = vertices.len() as u32;
+        vertices.extend(raw.vertices.chunks_exact(2).map(|v| Vec2::new(v[0], v[1])));
+        indices.extend(
+            raw.triangle_indices
+                .chunks_exact(3)
+                .map(|t| [t[0], t[1], t[2]].map(|i| base + i as u32)),
+        );
+    }
+    (vertices, indices)
+}
+
+impl From<&Shape> for Collider {
+    fn from(value: &Shape) -> Self {
         match value {
-            Shape::Circle(Circle { radius }) => Collider::circle(radius),
+            Shape::Circle(Circle { radius }) => Collider::circle(*radius),
             Shape::Rectangle(rect) => Collider::rectangle(rect.size().x, rect.size().y),
+            _ => {
+                let (vertices, indices) = triangulate(&value.to_polygons());
+                if indices.is_empty() {
+                    warn!("Shape {value:?} is empty; it will have a point collider");
+                    return Collider::circle(0.0);
+                }
+                Collider::trimesh(vertices, indices)
+            }
         }
     }
 }
@@ -37,7 +399,7 @@ impl ColliderFromShape {
         mut commands: Commands,
     ) {
         for (id, shape) in &q {
-            commands.entity(id).insert(Collider::from(*shape));
+            commands.entity(id).insert(Collider::from(shape));
         }
     }
 }
@@ -82,14 +444,288 @@ impl Stroke {
     pub fn new(color: Color, thickness: f32) -> Self { Self { color, thickness } }
 }
 
+/// Keeps a shape's origin and rotation attached to another entity, such as the enemy casting it or
+/// the player it's targeted on.
+///
+/// Anchored shapes are kept at the top level of the world rather than on an arena, and are
+/// despawned along with their targets.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect)]
+#[require(Transform)]
+pub struct ShapeAnchor {
+    pub target: Entity,
+    /// The position of the shape's origin relative to the target, turning as the target turns.
+    pub offset: Vec2,
+    /// The counterclockwise rotation of the shape from the way the target is facing, in degrees.
+    pub rotation: f32,
+}
+
+impl ShapeAnchor {
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            offset: Vec2::ZERO,
+            rotation: 0.0,
+        }
+    }
+
+    /// Produces where the shape goes when its target is at `position`, with a heading of `heading`
+    /// degrees clockwise from north.
+    pub fn placement(&self, position: Vec2, heading: f32) -> Isometry2d {
+        let turn = Rot2::degrees(-heading);
+        Isometry2d::new(
+            position + turn * self.offset,
+            turn * Rot2::degrees(self.rotation),
+        )
+    }
+
+    /// [System] that moves anchored shapes to follow their targets, and despawns those whose
+    /// targets are gone.
+    ///
+    /// Targets without a [`Facing`] are treated as facing north.
+    pub fn follow_targets(
+        anchor_q: Query<(Entity, &ShapeAnchor)>,
+        facing_q: Query<&Facing>,
+        mut transforms: ParamSet<(TransformHelper, Query<&mut Transform, With<ShapeAnchor>>)>,
+        mut commands: Commands,
+    ) {
+        for (id, anchor) in &anchor_q {
+            let Ok(target) = transforms.p0().world_position(anchor.target) else {
+                commands.entity(id).despawn_recursive();
+                continue;
+            };
+            let facing = facing_q.get(anchor.target).copied().unwrap_or_default();
+            let placement = anchor.placement(target, facing.heading());
+            let mut shapes = transforms.p1();
+            let Ok(mut transform) = shapes.get_mut(id) else {
+                continue;
+            };
+            let translation = placement.translation.extend(transform.translation.z);
+            let rotation = Quat::from_rotation_z(placement.rotation.as_radians());
+            if transform.translation != translation || transform.rotation != rotation {
+                transform.translation = translation;
+                transform.rotation = rotation;
+            }
+        }
+    }
+}
+
 pub struct ShapePlugin;
 
 impl Plugin for ShapePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, ColliderFromShape::update_colliders);
+        app.register_type::<ShapeAnchor>()
+            .add_systems(Update, ColliderFromShape::update_colliders)
+            .add_systems(
+                PostUpdate,
+                ShapeAnchor::follow_targets.before(TransformSystem::TransformPropagate),
+            );
         #[cfg(feature = "egui")]
         app.add_systems(Update, DrawShape::update_vector_shapes);
     }
 }
 
 pub fn plugin() -> ShapePlugin { ShapePlugin }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_shapes() {
+        let annulus: Shape =
+            ron::from_str("Annulus(inner_circle: (radius: 8.0), outer_circle: (radius: 20.0))")
+                .unwrap();
+        assert_eq!(annulus, Shape::Annulus(Annulus::new(8.0, 20.0)));
+
+        let compound: Shape = ron::from_str(
+            "Compound(union: [(shape: Circle(radius: 5.0), offset: Vec2(-10.0, 0.0))])",
+        )
+        .unwrap();
+        let Shape::Compound(compound) = compound else {
+            panic!("expected a compound, got {compound:?}");
+        };
+        assert_eq!(compound.union[0].shape, Shape::Circle(Circle::new(5.0)));
+        assert_eq!(compound.union[0].offset, Vec2::new(-10.0, 0.0));
+        assert!(compound.difference.is_empty());
+    }
+
+    #[test]
+    fn reject_unknown_fields() {
+        // A misspelled cone must not be read as a circle of the same radius.
+        assert!(ron::from_str::<Shape>("Cone(radius: 40.0, angel: 90.0)").is_err());
+        assert!(ron::from_str::<Shape>("Circle(radius: 20.0, center: (1.0, 1.0))").is_err());
+        assert!(ron::from_str::<Shape>("Line(length: 40.0, widht: 10.0)").is_err());
+        assert!(ron::from_str::<Shape>(
+            "Compound(union: [(shape: Circle(radius: 5.0), offest: Vec2(-10.0, 0.0))])"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn compound_contains() {
+        // A 20x20 square with a diamond cut out of the middle.
+        let shape = Shape::Compound(Compound {
+            union: vec![ShapePart {
+                shape: Shape::Rectangle(Rectangle::from_length(20.0)),
+                offset: Vec2::ZERO,
+                rotation: 0.0,
+            }],
+            difference: vec![ShapePart {
+                shape: Shape::Rectangle(Rectangle::from_length(4.0)),
+                offset: Vec2::ZERO,
+                rotation: 45.0,
+            }],
+        });
+        assert!(shape.contains(Vec2::new(9.0, 9.0)));
+        assert!(shape.contains(Vec2::new(2.5, 0.5)));
+        assert!(!shape.contains(Vec2::new(2.0, 0.0)));
+        assert!(!shape.contains(Vec2::new(11.0, 0.0)));
+
+        let polygons = shape.to_polygons();
+        assert!(polygons.contains(&to_coord(Vec2::new(9.0, 9.0))));
+        assert!(!polygons.contains(&to_coord(Vec2::new(1.0, 0.0))));
+        assert_eq!(polygons.0.len(), 1);
+        assert_eq!(polygons.0[0].interiors().len(), 1);
+    }
+
+    #[test]
+    fn parse_aoe_shapes() {
+        let cone: Shape = ron::from_str("Cone(radius: 40.0, angle: 90.0)").unwrap();
+        assert_eq!(
+            cone,
+            Shape::Cone(Cone {
+                radius: 40.0,
+                angle: 90.0
+            })
+        );
+        let circle: Shape = ron::from_str("Circle(radius: 40.0)").unwrap();
+        assert_eq!(circle, Shape::Circle(Circle::new(40.0)));
+        let cross: Shape = ron::from_str("Cross(arm_length: 40.0, width: 8.0)").unwrap();
+        assert_eq!(
+            cross,
+            Shape::Cross(Cross {
+                arm_length: 40.0,
+                width: 8.0
+            })
+        );
+        let line: Shape = ron::from_str("Line(length: 40.0, width: 10.0)").unwrap();
+        assert_eq!(
+            line,
+            Shape::Line(Line {
+                length: 40.0,
+                width: 10.0
+            })
+        );
+        let half: Shape = ron::from_str("HalfRoom(extent: 40.0)").unwrap();
+        assert_eq!(half, Shape::HalfRoom(HalfRoom { extent: 40.0 }));
+    }
+
+    #[test]
+    fn aoe_contains() {
+        let shapes = [
+            Shape::Cone(Cone {
+                radius: 10.0,
+                angle: 90.0,
+            }),
+            Shape::Line(Line {
+                length: 10.0,
+                width: 4.0,
+            }),
+            Shape::Cross(Cross {
+                arm_length: 10.0,
+                width: 4.0,
+            }),
+            Shape::HalfRoom(HalfRoom { extent: 10.0 }),
+        ];
+        // Points that are in each shape, and points that aren't.
+        let inside = [
+            vec![Vec2::new(0.0, 9.0), Vec2::new(3.0, 4.0)],
+            vec![Vec2::new(1.5, 0.5), Vec2::new(-1.5, 9.5)],
+            vec![Vec2::new(9.0, 1.0), Vec2::new(-1.0, -9.0)],
+            vec![Vec2::new(-9.0, 0.5), Vec2::new(9.0, 9.0)],
+        ];
+        let outside = [
+            vec![
+                Vec2::new(5.0, 4.0),
+                Vec2::new(0.0, -1.0),
+                Vec2::new(0.0, 10.5),
+            ],
+            vec![Vec2::new(2.5, 5.0), Vec2::new(0.0, -0.5)],
+            vec![Vec2::new(5.0, 5.0), Vec2::new(10.5, 0.0)],
+            vec![Vec2::new(0.0, -0.5), Vec2::new(10.5, 5.0)],
+        ];
+        for ((shape, inside), outside) in shapes.iter().zip(inside).zip(outside) {
+            let polygons = shape.to_polygons();
+            for point in inside {
+                assert!(shape.contains(point), "{shape:?} should contain {point}");
+                assert!(
+                    polygons.contains(&to_coord(point)),
+                    "{shape:?} polygons at {point}"
+                );
+            }
+            for point in outside {
+                assert!(
+                    !shape.contains(point),
+                    "{shape:?} shouldn't contain {point}"
+                );
+                assert!(
+                    !polygons.contains(&to_coord(point)),
+                    "{shape:?} polygons at {point}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn edge_distances() {
+        let circle = Shape::Circle(Circle::new(5.0));
+        assert_eq!(circle.edge_distance(Vec2::new(3.0, 0.0)), 2.0);
+        assert_eq!(circle.edge_distance(Vec2::new(0.0, -8.0)), 3.0);
+
+        let donut = Shape::Annulus(Annulus::new(8.0, 20.0));
+        assert_eq!(donut.edge_distance(Vec2::new(10.0, 0.0)), 2.0);
+        assert_eq!(donut.edge_distance(Vec2::ZERO), 8.0);
+
+        let line = Shape::Line(Line {
+            length: 10.0,
+            width: 4.0,
+        });
+        assert!((line.edge_distance(Vec2::new(0.5, 5.0)) - 1.5).abs() < 1e-4);
+        assert!((line.edge_distance(Vec2::new(0.0, 13.0)) - 3.0).abs() < 1e-4);
+
+        // The far side of the room is nearer than the side it's cast from.
+        let half = Shape::HalfRoom(HalfRoom { extent: 10.0 });
+        assert!((half.edge_distance(Vec2::new(2.0, 6.0)) - 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn anchor_placement() {
+        let anchor = ShapeAnchor {
+            target: Entity::PLACEHOLDER,
+            offset: Vec2::new(0.0, 2.0),
+            rotation: 90.0,
+        };
+        // Facing east, the offset points east and the shape, which points north when
+        // unrotated, points north again after turning a quarter clockwise then counterclockwise.
+        let placement = anchor.placement(Vec2::new(10.0, 0.0), 90.0);
+        assert!(placement
+            .translation
+            .abs_diff_eq(Vec2::new(12.0, 0.0), 1e-4));
+        assert!((placement.rotation * Vec2::Y).abs_diff_eq(Vec2::Y, 1e-4));
+    }
+
+    #[test]
+    fn polygon_contains() {
+        let triangle = Shape::Polygon(Polygon {
+            vertices: vec![
+                Vec2::new(0.0, 10.0),
+                Vec2::new(-10.0, -10.0),
+                Vec2::new(10.0, -10.0),
+            ],
+        });
+        assert!(triangle.contains(Vec2::ZERO));
+        assert!(!triangle.contains(Vec2::new(8.0, 8.0)));
+        let (_, indices) = triangulate(&triangle.to_polygons());
+        assert_eq!(indices.len(), 1);
+    }
+}
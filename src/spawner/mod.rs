@@ -17,7 +17,6 @@ use itertools::Itertools;
 #[cfg(feature = "egui")]
 use crate::ui::widget::{widget, InitWidget, WidgetCtx, WidgetSystemId};
 use crate::{
-    arena::Arena,
     ecs::{EntityExts, EntityExtsOf, NestedSystemExts},
     image::{DrawImage, DrawImageKind},
 };
@@ -43,14 +42,20 @@ const SPAWNER_ALPHA: u8 = 230;
 const SPAWNER_DISABLED_ALPHA: u8 = 25;
 
 /// An entity that can be spawned.
-pub trait Spawnable: Component + Reflect + TypePath + Clone + PartialEq + Debug + Ord {
+pub trait Spawnable: Component + Reflect + TypePath + Clone + PartialEq + Debug {
     const UNIQUE: bool;
     const Z: f32;
 
+    /// What the spawners in a panel are sorted by.
+    ///
+    /// Only unique spawnables have a panel of several spawners to sort.
+    type SortKey: Ord;
+
     fn size() -> Vec2;
     fn sep() -> Vec2;
 
     fn spawner_name(&self) -> Cow<'static, str>;
+    fn sort_key(&self) -> Self::SortKey;
     fn insert(&self, entity: &mut EntityCommands);
 }
 
@@ -110,15 +115,14 @@ impl<T: Spawnable> Spawner<T> {
     /// Technically what it actually does is, to preserve continuity of the drag event,
     /// replaces this entity with the new waymark, and spawns a new [Spawner] in its place.
     ///
-    /// The new entity will be a child of the current arena.
+    /// The new entity will become a child of whichever arena it is dropped on.
     ///
-    /// Panics if there is more than one camera or arena.
+    /// Panics if there is more than one camera.
     pub fn start_drag(
         ev: Trigger<Pointer<DragStart>>,
         spawner_q: Query<(&Spawner<T>, Option<&Parent>)>,
         #[cfg(feature = "egui")] camera_q: Single<(&Camera, &GlobalTransform)>,
         children_q: Query<&mut Children>,
-        arena_q: Option<Single<Entity, With<Arena>>>,
         mut commands: Commands,
     ) {
         let id = ev.entity();
@@ -138,7 +142,7 @@ impl<T: Spawnable> Spawner<T> {
         }
 
         let mut entity = commands.entity(id);
-        entity.remove::<(Self, Name)>();
+        entity.remove::<(Self, Name)>().remove_parent();
         spawner.target.insert(&mut entity);
 
         #[cfg(feature = "egui")]
@@ -157,12 +161,6 @@ impl<T: Spawnable> Spawner<T> {
             entity.insert(Transform::from_translation(translation.with_z(T::Z)));
         }
 
-        if let Some(arena_id) = arena_q {
-            entity.set_parent(*arena_id);
-        } else {
-            warn!("Spawner {:?} spawning an entity without a parent", id);
-        }
-
         // Forward to the general dragging implementation.
         commands.run_system_cached_with(crate::drag::start_drag, id);
     }
@@ -53,7 +53,7 @@ impl<T: Spawnable> SpawnerPanel<T> {
         spawner_q: Query<&Spawner<T>>,
     ) {
         for mut children in &mut q {
-            children.sort_by_cached_key(|&id| spawner_q.get(id).map(|s| &s.target).ok())
+            children.sort_by_cached_key(|&id| spawner_q.get(id).map(|s| s.target.sort_key()).ok())
         }
     }
 }
@@ -0,0 +1,108 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_egui::egui::{self, RichText, TextEdit};
+
+use super::{Strat, DIR, EXTENSION};
+use crate::ui::{
+    menu::TopMenu,
+    widget::{widget, InitWidget, WidgetCtx},
+    UiSortKey,
+};
+
+/// Top menu for saving and opening strats.
+#[derive(Component, Default, Debug)]
+#[require(InitWidget(|| widget!()))]
+pub struct StratMenu {
+    /// The name to save the current strat under.
+    name: String,
+}
+
+impl StratMenu {
+    pub fn show(
+        WidgetCtx { ns: _ns, id, ui }: WidgetCtx,
+        mut menu_q: Query<&mut StratMenu>,
+        #[cfg(not(target_arch = "wasm32"))] args: Res<crate::Args>,
+        mut commands: Commands,
+    ) {
+        let mut menu = menu_q.get_mut(id).unwrap();
+        ui.menu_button("Strat", |ui| {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                ui.horizontal(|ui| {
+                    ui.label("Name: ");
+                    ui.add(TextEdit::singleline(&mut menu.name).desired_width(120.0));
+                });
+                if ui
+                    .add_enabled(!menu.name.is_empty(), egui::Button::new("Save"))
+                    .clicked()
+                {
+                    commands.run_system_cached_with(Strat::save, menu.name.clone());
+                    ui.close_menu();
+                }
+                ui.separator();
+                ui.menu_button("Open", |ui| {
+                    // This is only generated while the menu is open, so it's fine to do it
+                    // every frame, and it picks up newly-saved strats.
+                    let listing = tataru::generate_listing(super::strat_dir(&args), EXTENSION)
+                        .ok()
+                        .filter(|l| !l.contents.is_empty() || !l.subdirs.is_empty());
+                    if let Some(listing) = listing {
+                        let dir = Path::new(DIR);
+                        Self::submenu(ui, &listing, dir, &mut menu.name, &mut commands);
+                    } else {
+                        ui.label(RichText::new("No saved strats").italics());
+                    }
+                });
+            }
+            #[cfg(target_arch = "wasm32")]
+            ui.label(RichText::new("Saving strats is not yet supported on the web.").italics());
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn submenu(
+        ui: &mut egui::Ui,
+        listing: &tataru::Listing,
+        dir: &Path,
+        name: &mut String,
+        commands: &mut Commands,
+    ) {
+        for (subdir_name, subdir) in &listing.subdirs {
+            ui.menu_button(subdir_name.clone(), |ui| {
+                Self::submenu(ui, subdir, &dir.join(subdir_name), name, commands);
+            });
+        }
+        if !listing.subdirs.is_empty() && !listing.contents.is_empty() {
+            ui.separator();
+        }
+        for file in &listing.contents {
+            let stem = file.strip_suffix(&format!(".{EXTENSION}")).unwrap_or(file);
+            if ui.button(stem).clicked() {
+                *name = stem.to_owned();
+                commands.run_system_cached_with(Strat::open, dir.join(file));
+                ui.close_menu();
+            }
+        }
+    }
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct StratMenuPlugin;
+
+impl Plugin for StratMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Startup,
+            |top: Single<Entity, With<TopMenu>>, mut commands: Commands| {
+                commands.entity(*top).with_child((
+                    StratMenu::default(),
+                    UiSortKey(0),
+                    Name::new("Strat Menu"),
+                ));
+            },
+        );
+    }
+}
+
+pub fn plugin() -> StratMenuPlugin { StratMenuPlugin }
@@ -0,0 +1,826 @@
+//! Strat documents.
+//!
+//! A strat is a saved copy of an entire board: the arena it is set in, and everything placed on it.
+//! Strats are stored as RON files with the `.strat.ron` extension and loaded as assets.
+//!
+//! All positions in a strat are stored in game coordinates, like waymark presets,
+//! so that they do not depend on how stratmat happens to lay out the board.
+//! A strat can have several arenas, in which case each position is relative to the offset of the
+//! arena it is on.
+
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{asset::AssetLoader, prelude::*};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    aoe::{Aoe, AoeRule},
+    arena::{Arena, ArenaMeta, Arenas, GameCoordOffset},
+    asset::{AssetHookExt, LifecycleExts},
+    enemy::{Enemy, ENEMY_Z},
+    hitbox::{Hitbox, HitboxKind, RangeRings},
+    movement::{MoveTarget, Movement},
+    player::{
+        facing::Facing,
+        marker::{StatusMarker, StatusMarkers},
+        party::{Party, PartySlot},
+        Player, PLAYER_Z,
+    },
+    shape::{DrawShape, Shape, ShapeAnchor},
+    tether::{Tether, TetherRule},
+    waymark::{PresetEntry, Waymark, Waymarks},
+};
+
+#[cfg(feature = "egui")]
+mod menu_egui;
+pub mod menu {
+    #[cfg(feature = "egui")]
+    pub use super::menu_egui::*;
+}
+
+/// The file extension of `Strat` files.
+const EXTENSION: &str = "strat.ron";
+/// The path, relative to the assets directory, to the directory where `Strat` files are stored.
+const DIR: &str = "strats";
+
+/// The current version of the strat format.
+///
+/// Strats with a newer version than this will refuse to load.
+pub const VERSION: u32 = 1;
+
+/// The Z-coordinate of shapes spawned from a strat.
+const SHAPE_Z: f32 = 50.0;
+
+/// Get the asset path for a strat, given its path minus the
+/// constant directory and extension parts.
+pub fn asset_path(strat: impl AsRef<Path>) -> PathBuf {
+    let mut path = PathBuf::new();
+    path.push(DIR);
+    path.push(strat);
+    path.set_extension(EXTENSION);
+    path
+}
+
+/// A saved board.
+#[derive(Asset, Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct Strat {
+    /// The version of the strat format this was saved with.
+    pub version: u32,
+    pub name: String,
+    /// The asset path of the [`ArenaMeta`] the strat is set in.
+    pub arena: String,
+    /// Any further arenas on the board alongside the main one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_arenas: Vec<ArenaDoc>,
+    /// The arena that the waymarks are on.
+    #[serde(default, skip_serializing_if = "is_main_arena")]
+    pub waymark_arena: usize,
+    #[serde(default)]
+    pub waymarks: Vec<PresetEntry>,
+    /// The party composition.
+    ///
+    /// Strats saved without one leave the current party as it is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub party: Option<Party>,
+    #[serde(default)]
+    pub players: Vec<PlayerDoc>,
+    #[serde(default)]
+    pub enemies: Vec<EnemyDoc>,
+    #[serde(default)]
+    pub shapes: Vec<ShapeDoc>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tethers: Vec<TetherDoc>,
+}
+
+/// An extra arena in a strat.
+///
+/// Everything on the board refers to its arena by index:
+/// 0 is the main [`Strat::arena`], and `n` is `extra_arenas[n - 1]`.
+#[derive(Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct ArenaDoc {
+    /// The asset path of the [`ArenaMeta`].
+    pub arena: String,
+    /// The position of the arena's center on the board, relative to the main arena's center.
+    pub position: Vec2,
+}
+
+fn is_main_arena(index: &usize) -> bool { *index == 0 }
+
+/// A saved [`Player`].
+#[derive(Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerDoc {
+    /// The player's slot in the party.
+    pub slot: PartySlot,
+    /// The direction the player is facing.
+    #[serde(default)]
+    pub facing: Facing,
+    /// The markers over the player's head.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<StatusMarker>,
+    /// The arena the player is on.
+    #[serde(default, skip_serializing_if = "is_main_arena")]
+    pub arena: usize,
+    /// The in-game (X, Z) coordinates of the player.
+    pub position: Vec2,
+}
+
+/// A saved [`Enemy`], along with its [`Hitbox`].
+#[derive(Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct EnemyDoc {
+    pub name: String,
+    pub kind: HitboxKind,
+    pub color: Color,
+    pub outer_radius: f32,
+    pub inner_radius: f32,
+    /// The direction the enemy is facing.
+    #[serde(default)]
+    pub facing: Facing,
+    /// The asset path of the enemy's icon.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// The range rings drawn around the enemy.
+    #[serde(default, skip_serializing_if = "RangeRings::is_empty")]
+    pub rings: RangeRings,
+    /// The arena the enemy is on.
+    #[serde(default, skip_serializing_if = "is_main_arena")]
+    pub arena: usize,
+    /// The in-game (X, Z) coordinates of the enemy.
+    pub position: Vec2,
+}
+
+/// A saved free-standing or anchored shape.
+#[derive(Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct ShapeDoc {
+    pub shape: Shape,
+    pub draw: DrawShape,
+    /// The counterclockwise rotation of the shape, in degrees.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub rotation: f32,
+    /// What the shape is anchored to, if anything.
+    ///
+    /// An anchored shape is placed by its anchor, and its `arena`, `position` and `rotation` are
+    /// ignored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<AnchorDoc>,
+    /// Whether the shape is an [`Aoe`], which hits the players inside it.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub aoe: bool,
+    /// Who should be hit by the shape, if it matters. A shape with a rule is always an [`Aoe`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<AoeRule>,
+    /// The arena the shape is on.
+    #[serde(default, skip_serializing_if = "is_main_arena")]
+    pub arena: usize,
+    /// The in-game (X, Z) coordinates of the shape's origin.
+    pub position: Vec2,
+}
+
+fn is_zero(value: &f32) -> bool { *value == 0.0 }
+
+fn is_false(value: &bool) -> bool { !*value }
+
+/// A saved [`ShapeAnchor`].
+#[derive(Reflect, Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct AnchorDoc {
+    pub target: StratRef,
+    #[serde(default)]
+    pub offset: Vec2,
+    #[serde(default)]
+    pub rotation: f32,
+}
+
+/// A saved [`Tether`].
+#[derive(Reflect, Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct TetherDoc {
+    pub from: StratRef,
+    pub to: StratRef,
+    pub rule: TetherRule,
+}
+
+/// A player or enemy in a saved strat, such as one end of a [`Tether`].
+#[derive(Reflect, Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StratRef {
+    /// The player in the given party slot.
+    Player(PartySlot),
+    /// The enemy at the given index in [`Strat::enemies`].
+    Enemy(usize),
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct StratLoader;
+
+#[derive(Error, Debug)]
+pub enum StratLoadError {
+    #[error("Could not load strat file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Could not parse strat file: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("Strat file has version {0}, but only up to version {VERSION} is supported")]
+    Version(u32),
+}
+
+impl AssetLoader for StratLoader {
+    type Asset = Strat;
+    type Settings = ();
+    type Error = StratLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn bevy::asset::io::Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut bevy::asset::LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).await?;
+        let data: Strat = ron::de::from_bytes(&buf)?;
+        if data.version > VERSION {
+            return Err(StratLoadError::Version(data.version));
+        }
+        Ok(data)
+    }
+
+    fn extensions(&self) -> &[&str] { &[EXTENSION] }
+}
+
+#[derive(Error, Debug)]
+pub enum StratSaveError {
+    #[error("No arena is loaded")]
+    NoArena,
+    #[error("Could not serialize strat: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("Could not write strat file: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl Strat {
+    /// [System] that captures the current board as a [`Strat`] with the given name.
+    ///
+    /// The leftmost arena is the main one. Anything that isn't on an arena is saved as if it were
+    /// on the main arena.
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub fn collect(
+        In(name): In<String>,
+        arenas: Arenas,
+        waymarks: Waymarks,
+        party: Res<Party>,
+        player_q: Query<(Entity, &Player, &Facing, &StatusMarkers)>,
+        enemy_q: Query<(Entity, &Enemy, &Hitbox, &Facing, &RangeRings)>,
+        shape_q: Query<(
+            Entity,
+            &Shape,
+            &DrawShape,
+            Has<Aoe>,
+            Option<&AoeRule>,
+            &Transform,
+            Option<&Parent>,
+        )>,
+        anchor_q: Query<&ShapeAnchor>,
+        tether_q: Query<&Tether>,
+    ) -> Result<Strat, StratSaveError> {
+        let board = arenas.all();
+        let Some(&(main_id, main)) = board.first() else {
+            return Err(StratSaveError::NoArena);
+        };
+        let index_of = |arena: Entity| board.iter().position(|&(id, _)| id == arena);
+        // Produces the index of the arena an entity is on, and its game coordinates there.
+        let locate = |id: Entity| -> Option<(usize, Vec2)> {
+            let (arena_id, arena) = arenas.arena_of(id).unwrap_or((main_id, main));
+            let transform = arenas.transform_on(id, arena_id)?;
+            let position = arena
+                .offset()
+                .world_to_game(transform.translation.truncate());
+            Some((index_of(arena_id)?, position))
+        };
+
+        let (waymark_arena, waymarks) = match (waymarks.arena(), waymarks.entries()) {
+            (Some((id, _)), Some((_, entries))) => {
+                (index_of(id).unwrap_or(0), entries.into_values().collect())
+            }
+            _ => (0, vec![]),
+        };
+
+        let enemies = enemy_q
+            .iter()
+            .filter_map(|(id, enemy, hitbox, &facing, rings)| {
+                let (arena, position) = locate(id)?;
+                Some((id, EnemyDoc {
+                    name: enemy.name.clone(),
+                    kind: hitbox.kind,
+                    color: hitbox.color,
+                    outer_radius: hitbox.outer_radius,
+                    inner_radius: hitbox.inner_radius,
+                    facing,
+                    icon: enemy.icon.clone(),
+                    rings: rings.clone(),
+                    arena,
+                    position,
+                }))
+            })
+            .collect_vec();
+        // Players are referred to by slot, and enemies by their index in the strat.
+        let ref_of = |id: Entity| match player_q.get(id) {
+            Ok((_, player, ..)) => Some(StratRef::Player(player.slot)),
+            Err(_) => enemies
+                .iter()
+                .position(|&(enemy, _)| enemy == id)
+                .map(StratRef::Enemy),
+        };
+        let tethers = tether_q
+            .iter()
+            .filter_map(|tether| {
+                Some(TetherDoc {
+                    from: ref_of(tether.from)?,
+                    to: ref_of(tether.to)?,
+                    rule: tether.rule,
+                })
+            })
+            .collect();
+
+        Ok(Strat {
+            version: VERSION,
+            name,
+            arena: main.asset_path.clone(),
+            extra_arenas: board[1..]
+                .iter()
+                .filter_map(|&(id, arena)| {
+                    Some(ArenaDoc {
+                        arena: arena.asset_path.clone(),
+                        position: arenas.transform_on(id, main_id)?.translation.truncate(),
+                    })
+                })
+                .collect(),
+            waymark_arena,
+            waymarks,
+            party: Some(party.clone()),
+            players: player_q
+                .iter()
+                .filter_map(|(id, player, &facing, markers)| {
+                    let (arena, position) = locate(id)?;
+                    Some(PlayerDoc {
+                        slot: player.slot,
+                        facing,
+                        markers: markers.0.clone(),
+                        arena,
+                        position,
+                    })
+                })
+                .collect(),
+            enemies: enemies.into_iter().map(|(_, enemy)| enemy).collect(),
+            shapes: shape_q
+                .iter()
+                .filter_map(|(id, shape, draw, aoe, rule, transform, parent)| {
+                    let anchor = match anchor_q.get(id) {
+                        Ok(anchor) => Some(AnchorDoc {
+                            target: ref_of(anchor.target)?,
+                            offset: anchor.offset,
+                            rotation: anchor.rotation,
+                        }),
+                        // Free-standing shapes are only saved if they're on an arena.
+                        Err(_) if parent.is_some_and(|parent| index_of(parent.get()).is_some()) => {
+                            None
+                        }
+                        Err(_) => return None,
+                    };
+                    let (arena, position) = locate(id)?;
+                    Some(ShapeDoc {
+                        shape: shape.clone(),
+                        draw: *draw,
+                        rotation: transform.rotation.to_euler(EulerRot::ZYX).0.to_degrees(),
+                        anchor,
+                        aoe,
+                        rule: rule.copied(),
+                        arena,
+                        position,
+                    })
+                })
+                .collect(),
+            tethers,
+        })
+    }
+
+    /// Produces the asset paths of all of the strat's arenas, in index order.
+    pub fn arena_paths(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.arena.as_str())
+            .chain(self.extra_arenas.iter().map(|extra| extra.arena.as_str()))
+    }
+
+    /// Spawns everything in this strat onto the given arenas, in index order,
+    /// which must have been spawned with the given [`GameCoordOffset`]s.
+    pub fn spawn_on(&self, commands: &mut Commands, arenas: &[(Entity, GameCoordOffset)]) {
+        let arena = |index: usize| {
+            let arena = arenas.get(index).copied();
+            if arena.is_none() {
+                warn!("Strat '{}' refers to missing arena {index}", self.name);
+            }
+            arena
+        };
+
+        if let Some((id, _)) = arena(self.waymark_arena) {
+            Waymark::spawn_from_entries(commands, self.waymarks.iter().cloned(), id);
+        }
+
+        let mut players = BTreeMap::new();
+        for player in &self.players {
+            let Some((id, offset)) = arena(player.arena) else {
+                continue;
+            };
+            let pos = offset.game_to_world(player.position);
+            let player_id = commands
+                .spawn((
+                    Player { slot: player.slot },
+                    player.facing,
+                    StatusMarkers(player.markers.clone()),
+                    Transform::from_translation(pos.extend(PLAYER_Z)),
+                ))
+                .set_parent(id)
+                .id();
+            players.insert(player.slot, player_id);
+        }
+
+        let mut enemies = Vec::with_capacity(self.enemies.len());
+        for enemy in &self.enemies {
+            let Some((id, offset)) = arena(enemy.arena) else {
+                enemies.push(None);
+                continue;
+            };
+            let pos = offset.game_to_world(enemy.position);
+            let enemy_id = commands
+                .spawn((
+                    Enemy {
+                        name: enemy.name.clone(),
+                        kind: enemy.kind,
+                        radius: enemy.outer_radius,
+                        color: enemy.color,
+                        icon: enemy.icon.clone(),
+                    },
+                    Hitbox {
+                        kind: enemy.kind,
+                        color: enemy.color,
+                        outer_radius: enemy.outer_radius,
+                        inner_radius: enemy.inner_radius,
+                    },
+                    enemy.facing,
+                    enemy.rings.clone(),
+                    Transform::from_translation(pos.extend(ENEMY_Z)),
+                ))
+                .set_parent(id)
+                .id();
+            enemies.push(Some(enemy_id));
+        }
+
+        let entity_of = |target: StratRef| match target {
+            StratRef::Player(slot) => players.get(&slot).copied(),
+            StratRef::Enemy(index) => enemies.get(index).copied().flatten(),
+        };
+
+        for shape in &self.shapes {
+            let bundle = (Name::new("Shape"), shape.shape.clone(), shape.draw);
+            let spawned = if let Some(anchor) = shape.anchor {
+                let Some(target) = entity_of(anchor.target) else {
+                    warn!(
+                        "Strat '{}' has a shape anchored to something missing",
+                        self.name
+                    );
+                    continue;
+                };
+                commands
+                    .spawn((
+                        bundle,
+                        ShapeAnchor {
+                            target,
+                            offset: anchor.offset,
+                            rotation: anchor.rotation,
+                        },
+                        Transform::from_xyz(0.0, 0.0, SHAPE_Z),
+                    ))
+                    .id()
+            } else {
+                let Some((id, offset)) = arena(shape.arena) else {
+                    continue;
+                };
+                let pos = offset.game_to_world(shape.position);
+                commands
+                    .spawn((
+                        bundle,
+                        Transform::from_translation(pos.extend(SHAPE_Z))
+                            .with_rotation(Quat::from_rotation_z(shape.rotation.to_radians())),
+                    ))
+                    .set_parent(id)
+                    .id()
+            };
+            if shape.aoe {
+                commands.entity(spawned).insert(Aoe);
+            }
+            if let Some(rule) = shape.rule {
+                commands.entity(spawned).insert(rule);
+            }
+        }
+
+        for tether in &self.tethers {
+            let (Some(from), Some(to)) = (entity_of(tether.from), entity_of(tether.to)) else {
+                warn!("Strat '{}' has a tether to something missing", self.name);
+                continue;
+            };
+            commands.spawn((Tether::new(from, to, tether.rule), Name::new("Tether")));
+        }
+    }
+
+    /// [System] that opens the strat at the given asset path, replacing the current board.
+    ///
+    /// The board is only replaced once the strat and its arena have both finished loading.
+    pub fn open(In(path): In<PathBuf>, asset_server: Res<AssetServer>, mut commands: Commands) {
+        info!("Opening strat {}", path.display());
+        let handle = asset_server.load::<Strat>(path);
+        commands.on_asset_loaded_with(handle.clone(), Self::load_arenas, handle);
+    }
+
+    /// Second stage of [`Strat::open`]: load the strat's arenas.
+    fn load_arenas(
+        In(handle): In<Handle<Strat>>,
+        strats: Res<Assets<Strat>>,
+        asset_server: Res<AssetServer>,
+        mut commands: Commands,
+    ) {
+        let Some(strat) = strats.get(&handle) else {
+            error!("Unable to open strat: it was unloaded before it could be used");
+            return;
+        };
+        let arenas = strat
+            .arena_paths()
+            .map(|path| asset_server.load::<ArenaMeta>(path))
+            .collect_vec();
+        commands.run_system_cached_with(Self::replace_board, (arenas, strat.clone()));
+    }
+
+    /// Final stage of [`Strat::open`]: once all of its arenas have loaded,
+    /// replace the board with the strat's contents.
+    fn replace_board(
+        In((handles, strat)): In<(Vec<Handle<ArenaMeta>>, Strat)>,
+        arenas: Res<Assets<ArenaMeta>>,
+        party: Res<Party>,
+        mut commands: Commands,
+    ) {
+        if let Some(pending) = handles.iter().find(|handle| !arenas.contains(*handle)) {
+            commands.on_asset_loaded_with(pending.clone(), Self::replace_board, (handles, strat));
+            return;
+        }
+        info!("Opened strat '{}'", strat.name);
+
+        commands.run_system_cached(despawn_board);
+        let party = strat.party.clone().unwrap_or_else(|| party.clone());
+        commands.insert_resource(party);
+        let positions =
+            std::iter::once(Vec2::ZERO).chain(strat.extra_arenas.iter().map(|doc| doc.position));
+        let spawned = handles
+            .iter()
+            .filter_map(|handle| arenas.get(handle))
+            .zip(positions)
+            .map(|(arena, position)| {
+                let id = Arena::spawn(&mut commands, arena.clone(), position);
+                (id, GameCoordOffset(arena.offset))
+            })
+            .collect_vec();
+        strat.spawn_on(&mut commands, &spawned);
+    }
+
+    /// [System] that saves the current board as a strat with the given name.
+    ///
+    /// The strat is written into the strats directory under the asset root,
+    /// and any loaded copy of it is reloaded.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(In(name): In<String>, world: &mut World) {
+        let run = |world: &mut World| -> Result<PathBuf, StratSaveError> {
+            let strat = world
+                .run_system_cached_with(Self::collect, name.clone())
+                .expect("Strat::collect should be runnable")?;
+            let ron = ron::ser::to_string_pretty(&strat, default())?;
+
+            let path = asset_path(file_stem(&name));
+            let full_path = asset_root(world.resource::<crate::Args>()).join(&path);
+            if let Some(dir) = full_path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(&full_path, ron)?;
+
+            world.resource::<AssetServer>().reload(path.clone());
+            Ok(full_path)
+        };
+        match run(world) {
+            Ok(path) => info!("Saved strat '{name}' to {}", path.display()),
+            Err(e) => error!("Unable to save strat '{name}': {e}"),
+        }
+    }
+}
+
+/// Produces a file name (without extension) for a strat with the given name.
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Produces the path to the directory that assets are loaded from.
+#[cfg(not(target_arch = "wasm32"))]
+fn asset_root(args: &crate::Args) -> PathBuf {
+    use bevy::asset::io::file::FileAssetReader;
+    args.asset_root
+        .clone()
+        .unwrap_or_else(|| FileAssetReader::get_base_path().join(AssetPlugin::default().file_path))
+}
+
+/// Produces the path to the directory that strats are saved in.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn strat_dir(args: &crate::Args) -> PathBuf { asset_root(args).join(DIR) }
+
+/// Despawns the entire board: every arena, everything placed on them, every enemy, every tether,
+/// every anchored shape and every movement.
+pub fn despawn_board(world: &mut World) {
+    type Roots = Or<(
+        With<Arena>,
+        With<Tether>,
+        With<ShapeAnchor>,
+        With<MoveTarget>,
+        With<Movement>,
+        (With<Enemy>, Without<Parent>),
+    )>;
+    let mut q = world.query_filtered::<Entity, Roots>();
+    for id in q.iter(world).collect_vec() {
+        world.entity_mut(id).despawn_recursive();
+    }
+}
+
+/// Plugin for strat support.
+#[derive(Default, Copy, Clone, Debug)]
+pub struct StratPlugin;
+
+impl Plugin for StratPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset_with_lifecycle::<Strat>()
+            .register_type::<Strat>()
+            .init_asset_loader::<StratLoader>();
+    }
+}
+
+pub fn plugin() -> StratPlugin { StratPlugin }
+
+#[cfg(test)]
+mod test {
+    use bevy::color::palettes::css::GOLD;
+
+    use super::*;
+    use crate::{
+        player::job::RANGED_RANGE,
+        shape::{Cone, Stroke},
+    };
+
+    #[test]
+    fn strat_ron_round_trip() {
+        let offset = Vec2::new(100.0, 100.0);
+        let strat = Strat {
+            version: VERSION,
+            name: "Test Strat".into(),
+            arena: "arenas/ultimate/fru/p1.arena.ron".into(),
+            extra_arenas: vec![],
+            waymark_arena: 0,
+            waymarks: vec![
+                Waymark::A.to_entry(&Transform::from_xyz(0.0, 12.0, 0.0), offset, None),
+                Waymark::Two.to_entry(&Transform::from_xyz(-3.5, -7.25, 0.0), offset, None),
+            ],
+            party: Some(Party::default()),
+            players: vec![PlayerDoc {
+                slot: PartySlot::MT,
+                facing: Facing::new(135.0),
+                markers: vec![StatusMarker::Stack, StatusMarker::LimitCut(2)],
+                arena: 0,
+                position: Vec2::new(100.0, 95.0),
+            }],
+            enemies: vec![EnemyDoc {
+                name: "Boss".into(),
+                kind: HitboxKind::Directional,
+                color: GOLD.into(),
+                outer_radius: 5.0,
+                inner_radius: 4.15,
+                facing: Facing::new(180.0),
+                icon: None,
+                rings: RangeRings {
+                    max_melee: true,
+                    rings: vec![RANGED_RANGE],
+                },
+                arena: 0,
+                position: offset,
+            }],
+            shapes: vec![
+                ShapeDoc {
+                    shape: Shape::Cone(Cone {
+                        radius: 40.0,
+                        angle: 90.0,
+                    }),
+                    draw: DrawShape::new_fill(Color::srgba(1.0, 0.5, 0.0, 0.4)),
+                    rotation: 0.0,
+                    anchor: Some(AnchorDoc {
+                        target: StratRef::Enemy(0),
+                        offset: Vec2::ZERO,
+                        rotation: 180.0,
+                    }),
+                    aoe: true,
+                    rule: Some(AoeRule::Tankbuster),
+                    arena: 0,
+                    position: offset,
+                },
+                ShapeDoc {
+                    shape: Shape::Circle(Circle::new(3.0)),
+                    draw: DrawShape::new_stroke(Stroke::new(Color::WHITE, 0.1)),
+                    rotation: 0.0,
+                    anchor: None,
+                    aoe: false,
+                    rule: None,
+                    arena: 0,
+                    position: offset,
+                },
+            ],
+            tethers: vec![TetherDoc {
+                from: StratRef::Player(PartySlot::MT),
+                to: StratRef::Enemy(0),
+                rule: TetherRule::Within(10.0),
+            }],
+        };
+
+        let ron = ron::ser::to_string_pretty(&strat, default()).unwrap();
+        let parsed: Strat = ron::de::from_str(&ron).unwrap();
+        assert_eq!(ron, ron::ser::to_string_pretty(&parsed, default()).unwrap());
+        assert_eq!(parsed.waymarks[1].waymark(), Some(Waymark::Two));
+        assert_eq!(parsed.party, Some(Party::default()));
+        assert_eq!(
+            parsed.shapes.iter().map(|shape| shape.aoe).collect_vec(),
+            vec![true, false]
+        );
+    }
+
+    #[test]
+    fn strat_missing_sections_default() {
+        let parsed: Strat =
+            ron::de::from_str(r#"(version: 1, name: "Empty", arena: "arenas/foo.arena.ron")"#)
+                .unwrap();
+        assert!(parsed.waymarks.is_empty());
+        assert!(parsed.players.is_empty());
+        assert_eq!(parsed.party, None);
+    }
+
+    #[test]
+    fn strat_extra_arenas() {
+        let parsed: Strat = ron::de::from_str(
+            r#"(
+                version: 1,
+                name: "Two Floors",
+                arena: "arenas/foo.arena.ron",
+                extra_arenas: [(arena: "arenas/bar.arena.ron", position: (45.0, 0.0))],
+                waymark_arena: 1,
+                players: [
+                    (slot: MT, position: (100.0, 100.0)),
+                    (slot: OT, arena: 1, position: (0.0, 0.0)),
+                ],
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.arena_paths().collect_vec(), vec![
+            "arenas/foo.arena.ron",
+            "arenas/bar.arena.ron"
+        ]);
+        assert_eq!(parsed.waymark_arena, 1);
+        assert_eq!(parsed.players.iter().map(|p| p.arena).collect_vec(), vec![
+            0, 1
+        ]);
+
+        // Main-arena indices are left out when saving.
+        let ron = ron::ser::to_string(&parsed).unwrap();
+        assert!(!ron.contains("arena:0"), "{ron}");
+        let reparsed: Strat = ron::de::from_str(&ron).unwrap();
+        assert_eq!(reparsed.players[1].arena, 1);
+    }
+
+    #[test]
+    fn despawn_board_clears_movements() {
+        let mut world = World::new();
+        let player = world.spawn(Transform::default()).id();
+        let target = world.spawn(MoveTarget).id();
+        let movement = world.spawn(Movement::new(player, target, default())).id();
+        despawn_board(&mut world);
+
+        // Move targets are spawned on their own rather than on an arena, and go with the board.
+        assert!(world.get_entity(target).is_err());
+        assert!(world.get_entity(movement).is_err());
+        assert!(world.get_entity(player).is_ok());
+    }
+}
@@ -0,0 +1,250 @@
+//! Tethers between players, or between a player and an enemy.
+//!
+//! A tether has a [`TetherRule`] saying how long it must be: some tethers must be stretched to
+//! break them or to keep their damage down, and others must be kept short. The tether is drawn as a
+//! line between its ends, labelled with its current length, and turns red while its rule is broken.
+
+use bevy::{
+    ecs::{component::ComponentId, world::DeferredWorld},
+    prelude::*,
+    transform::helper::TransformHelper,
+};
+#[cfg(feature = "egui")]
+use bevy_vector_shapes::{prelude::*, shapes::ShapeBundle};
+use serde::{Deserialize, Serialize};
+
+use crate::link::{self, Link};
+#[cfg(feature = "egui")]
+use crate::link::{link_label, LinkLabel};
+
+/// The Z-coordinate of tethers. They are drawn under players but over the arena.
+const TETHER_Z: f32 = 100.0;
+/// The thickness of the tether line.
+const TETHER_THICKNESS: f32 = 0.15;
+/// The color of a tether whose rule is met.
+const SATISFIED_COLOR: Color = Color::srgb(0.4, 0.85, 1.0);
+/// The color of a tether whose rule is broken.
+const VIOLATED_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+
+/// How long a tether must be.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Reflect, Serialize, Deserialize)]
+pub enum TetherRule {
+    /// The tether must be stretched to at least this many yalms.
+    Stretch(f32),
+    /// The tether must be kept within this many yalms.
+    Within(f32),
+}
+
+impl Default for TetherRule {
+    fn default() -> Self { TetherRule::Stretch(0.0) }
+}
+
+impl TetherRule {
+    /// Produces the length that the rule is measured against.
+    pub fn distance(self) -> f32 {
+        match self {
+            TetherRule::Stretch(distance) | TetherRule::Within(distance) => distance,
+        }
+    }
+
+    pub fn set_distance(&mut self, new: f32) {
+        match self {
+            TetherRule::Stretch(distance) | TetherRule::Within(distance) => *distance = new,
+        }
+    }
+
+    /// Returns true if a tether of the given length meets the rule.
+    pub fn is_satisfied(self, length: f32) -> bool {
+        match self {
+            TetherRule::Stretch(distance) => length >= distance,
+            TetherRule::Within(distance) => length <= distance,
+        }
+    }
+
+    /// Produces a short description of the rule, like "≥ 10y".
+    pub fn label(self) -> String {
+        match self {
+            TetherRule::Stretch(distance) => format!("≥ {distance}y"),
+            TetherRule::Within(distance) => format!("≤ {distance}y"),
+        }
+    }
+}
+
+/// A tether between two entities, such as two players or a player and an enemy.
+///
+/// The length of a tether is measured between the centers of its ends.
+#[derive(Component, Reflect, Copy, Clone, Debug, PartialEq)]
+#[require(Transform(|| Transform::from_xyz(0.0, 0.0, TETHER_Z)), Visibility, TetherLength)]
+#[component(on_add = Self::on_add)]
+pub struct Tether {
+    pub from: Entity,
+    pub to: Entity,
+    pub rule: TetherRule,
+}
+
+impl Link for Tether {
+    fn ends(&self) -> [Entity; 2] { [self.from, self.to] }
+}
+
+/// The current length of a [`Tether`], in yalms.
+#[derive(Component, Reflect, Default, Copy, Clone, Debug, PartialEq)]
+pub struct TetherLength(pub f32);
+
+/// Marker component for the line that draws a [`Tether`].
+#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
+pub struct TetherLine;
+
+impl Tether {
+    pub fn new(from: Entity, to: Entity, rule: TetherRule) -> Self { Self { from, to, rule } }
+
+    /// Returns true if the tether has the given entity at either end.
+    pub fn connects(&self, id: Entity) -> bool { self.from == id || self.to == id }
+
+    /// Produces the entity at the other end of the tether from the given one.
+    pub fn other(&self, id: Entity) -> Entity {
+        if self.from == id {
+            self.to
+        } else {
+            self.from
+        }
+    }
+
+    /// Adds the line and label.
+    fn on_add(mut world: DeferredWorld, id: Entity, _: ComponentId) {
+        #[cfg(feature = "egui")]
+        world.commands().entity(id).with_children(|parent| {
+            let config = ShapeConfig {
+                color: SATISFIED_COLOR,
+                thickness: TETHER_THICKNESS,
+                ..ShapeConfig::default_2d()
+            };
+            parent.spawn((
+                TetherLine,
+                ShapeBundle::line(&config, Vec3::ZERO, Vec3::ZERO),
+            ));
+            parent.spawn(link_label(SATISFIED_COLOR));
+        });
+    }
+
+    /// [System] that measures tethers.
+    pub fn update_lengths(mut q: Query<(&Tether, &mut TetherLength)>, helper: TransformHelper) {
+        for (tether, mut length) in &mut q {
+            let Some([from, to]) = tether.end_positions(&helper) else {
+                continue;
+            };
+            length.set_if_neq(TetherLength(from.distance(to)));
+        }
+    }
+
+    /// [System] that moves tether lines to follow their ends, and colors tethers by whether their
+    /// rules are met.
+    #[cfg(feature = "egui")]
+    pub fn update_drawings(
+        q: Query<(&Tether, &TetherLength, &Children)>,
+        helper: TransformHelper,
+        mut line_q: Query<(&mut LineComponent, &mut ShapeFill), With<TetherLine>>,
+        mut label_q: Query<(&mut Text2d, &mut TextColor), With<LinkLabel>>,
+    ) {
+        for (tether, length, children) in &q {
+            let Some([from, to]) = tether.end_positions(&helper) else {
+                continue;
+            };
+            let color = if tether.rule.is_satisfied(length.0) {
+                SATISFIED_COLOR
+            } else {
+                VIOLATED_COLOR
+            };
+
+            for &child in children {
+                if let Ok((mut line, mut fill)) = line_q.get_mut(child) {
+                    line.start = from.extend(0.0);
+                    line.end = to.extend(0.0);
+                    fill.color = color;
+                }
+                if let Ok((mut text, mut text_color)) = label_q.get_mut(child) {
+                    text.0 = format!("{:.1}y ({})", length.0, tether.rule.label());
+                    text_color.0 = color;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct TetherPlugin;
+
+impl Plugin for TetherPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(link::plugin::<Tether>())
+            .register_type::<Tether>()
+            .register_type::<TetherLength>()
+            .add_systems(
+                PostUpdate,
+                Tether::update_lengths.before(TransformSystem::TransformPropagate),
+            );
+        #[cfg(feature = "egui")]
+        app.add_systems(
+            PostUpdate,
+            Tether::update_drawings
+                .after(Tether::update_lengths)
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+pub fn plugin() -> TetherPlugin { TetherPlugin }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tether_rules() {
+        let stretch = TetherRule::Stretch(10.0);
+        assert!(stretch.is_satisfied(10.0));
+        assert!(stretch.is_satisfied(25.0));
+        assert!(!stretch.is_satisfied(9.9));
+
+        let mut within = TetherRule::Within(5.0);
+        assert!(within.is_satisfied(0.0));
+        assert!(!within.is_satisfied(5.1));
+        within.set_distance(6.0);
+        assert!(within.is_satisfied(5.1));
+        assert_eq!(within.label(), "≤ 6y");
+    }
+
+    #[test]
+    fn tethers_follow_their_ends() {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), TransformPlugin, plugin()));
+        let a = app
+            .world_mut()
+            .spawn(Transform::from_xyz(0.0, 0.0, 0.0))
+            .id();
+        let b = app
+            .world_mut()
+            .spawn(Transform::from_xyz(3.0, 4.0, 0.0))
+            .id();
+        let tether = app
+            .world_mut()
+            .spawn(Tether::new(a, b, TetherRule::Within(4.0)))
+            .id();
+        app.update();
+        assert_eq!(
+            app.world().get::<TetherLength>(tether),
+            Some(&TetherLength(5.0))
+        );
+
+        app.world_mut().get_mut::<Transform>(b).unwrap().translation = Vec3::new(0.0, 2.0, 0.0);
+        app.update();
+        assert_eq!(
+            app.world().get::<TetherLength>(tether),
+            Some(&TetherLength(2.0))
+        );
+
+        app.world_mut().despawn(a);
+        app.update();
+        assert!(app.world().get_entity(tether).is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use super::ViewRotation;
+use crate::ui::{
+    menu::TopMenu,
+    widget::{widget, InitWidget, WidgetCtx},
+    UiSortKey,
+};
+
+/// Top menu for rotating the view.
+#[derive(Component, Default, Debug)]
+#[require(InitWidget(|| widget!()))]
+pub struct ViewMenu;
+
+impl ViewMenu {
+    pub fn show(WidgetCtx { ui, .. }: WidgetCtx, mut view: ResMut<ViewRotation>) {
+        ui.menu_button("View", |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("↺ 45°").clicked() {
+                    view.step(-1);
+                }
+                if ui.button("↻ 45°").clicked() {
+                    view.step(1);
+                }
+            });
+            let mut heading = view.heading();
+            let slider = egui::Slider::new(&mut heading, 0.0..=359.0)
+                .suffix("°")
+                .text("Heading");
+            if ui.add(slider).changed() {
+                view.set_heading(heading);
+            }
+            ui.separator();
+            if ui.button("North Up").clicked() {
+                view.set_heading(0.0);
+                ui.close_menu();
+            }
+        });
+    }
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct ViewMenuPlugin;
+
+impl Plugin for ViewMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Startup,
+            |top: Single<Entity, With<TopMenu>>, mut commands: Commands| {
+                commands
+                    .entity(*top)
+                    .with_child((ViewMenu, UiSortKey(20), Name::new("View Menu")));
+            },
+        );
+    }
+}
+
+pub fn plugin() -> ViewMenuPlugin { ViewMenuPlugin }
@@ -0,0 +1,145 @@
+//! Rotation of the board view.
+//!
+//! The view can be rotated so that a strat can be taught relative to the boss, or from
+//! the party's point of view. Only the camera rotates; the board itself, and therefore
+//! every position that is saved or exported, stays in true game coordinates.
+
+use bevy::prelude::*;
+
+#[cfg(feature = "egui")]
+mod menu_egui;
+pub mod menu {
+    #[cfg(feature = "egui")]
+    pub use super::menu_egui::*;
+}
+
+/// The size of a rotation step, in degrees.
+pub const STEP_DEGREES: f32 = 45.0;
+
+/// The rotation of the board view.
+#[derive(Resource, Reflect, Default, Copy, Clone, Debug, PartialEq)]
+pub struct ViewRotation {
+    /// The compass heading at the top of the screen, in degrees clockwise from north.
+    ///
+    /// Always in the range `[0, 360)`.
+    heading: f32,
+}
+
+impl ViewRotation {
+    /// Produces a view with the given compass heading at the top of the screen.
+    pub fn new(heading: f32) -> Self {
+        Self {
+            heading: heading.rem_euclid(360.0),
+        }
+    }
+
+    /// Produces the compass heading at the top of the screen, in degrees clockwise from north.
+    pub fn heading(self) -> f32 { self.heading }
+
+    pub fn set_heading(&mut self, heading: f32) { *self = Self::new(heading); }
+
+    /// Rotates the view clockwise by the given number of 45° steps (anticlockwise if negative),
+    /// first snapping to the nearest step.
+    pub fn step(&mut self, steps: i32) {
+        let snapped = (self.heading / STEP_DEGREES).round();
+        self.set_heading((snapped + steps as f32) * STEP_DEGREES);
+    }
+
+    /// Produces the rotation to apply to the camera.
+    pub fn camera_rotation(self) -> Quat { Quat::from_rotation_z(-self.heading.to_radians()) }
+
+    /// [System] that applies the view rotation to the camera.
+    ///
+    /// FIXME: Single-camera assumption.
+    #[cfg(feature = "egui")]
+    pub fn rotate_camera(
+        view: Res<ViewRotation>,
+        mut camera_q: Query<&mut Transform, With<Camera2d>>,
+    ) {
+        for mut transform in &mut camera_q {
+            transform.rotation = view.camera_rotation();
+        }
+    }
+}
+
+/// Component for entities that should stay upright on screen however the view is rotated,
+/// such as icons and labels.
+#[derive(Component, Reflect, Default, Copy, Clone, Debug)]
+pub struct Upright;
+
+impl Upright {
+    /// [System] that counter-rotates upright entities against their parents and the view.
+    pub fn update_rotations(
+        view: Res<ViewRotation>,
+        mut q: Query<(Option<&Parent>, &mut Transform), With<Upright>>,
+        parent_q: Query<&GlobalTransform>,
+    ) {
+        for (parent, mut transform) in &mut q {
+            let parent_rotation = parent
+                .and_then(|parent| parent_q.get(parent.get()).ok())
+                .map_or(Quat::IDENTITY, |global| global.compute_transform().rotation);
+            let rotation = parent_rotation.inverse() * view.camera_rotation();
+            if transform.rotation != rotation {
+                transform.rotation = rotation;
+            }
+        }
+    }
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct ViewPlugin;
+
+impl Plugin for ViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ViewRotation>()
+            .register_type::<ViewRotation>()
+            .register_type::<Upright>()
+            .add_systems(
+                PostUpdate,
+                Upright::update_rotations.before(TransformSystem::TransformPropagate),
+            );
+        #[cfg(feature = "egui")]
+        app.add_systems(
+            PostUpdate,
+            ViewRotation::rotate_camera
+                .run_if(resource_changed::<ViewRotation>)
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+pub fn plugin() -> ViewPlugin { ViewPlugin }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn step_view() {
+        let mut view = ViewRotation::default();
+        view.step(1);
+        assert_eq!(view.heading(), 45.0);
+        view.step(-2);
+        assert_eq!(view.heading(), 315.0);
+
+        // Free rotations snap to the nearest step first.
+        view.set_heading(100.0);
+        view.step(1);
+        assert_eq!(view.heading(), 135.0);
+        view.set_heading(-370.0);
+        assert_eq!(view.heading(), 350.0);
+    }
+
+    #[test]
+    fn heading_is_up() {
+        // With east at the top, east in the world (+X) is up on screen.
+        let view = ViewRotation::new(90.0);
+        let up = view.camera_rotation() * Vec3::Y;
+        assert!(up.abs_diff_eq(Vec3::X, 1e-5), "{up}");
+
+        let view = ViewRotation::new(225.0);
+        let up = view.camera_rotation() * Vec3::Y;
+        let southwest = Vec3::new(-1.0, -1.0, 0.0).normalize();
+        assert!(up.abs_diff_eq(southwest, 1e-5), "{up}");
+    }
+}
@@ -0,0 +1,372 @@
+//! Support for the game's own saved waymark file, `FMARKER.DAT`.
+//!
+//! The game stores the waymark presets saved via the in-game Field Marker menu in this file,
+//! in each character's configuration folder.
+//! The layout matches the one used by the Waymark Preset plugin to read the same data from memory:
+//!
+//! - a header of [`HEADER_LEN`] bytes, which we preserve but don't interpret;
+//! - [`SLOTS`] slots of [`SLOT_LEN`] bytes each, all XORed with [`XOR_KEY`];
+//! - possibly some trailing bytes, which we also preserve.
+//!
+//! Each slot consists of:
+//!
+//! | Offset | Size | Contents                                                         |
+//! |--------|------|------------------------------------------------------------------|
+//! | 0      | 96   | 8 × (X, Y, Z) `i32`s, in thousandths of a yalm, in waymark order |
+//! | 96     | 1    | Bitmask of active waymarks, bit _n_ for waymark ID _n_           |
+//! | 97     | 1    | Unknown; preserved                                               |
+//! | 98     | 2    | `u16` map ID (the same ID the plugin calls `MapID`)              |
+//! | 100    | 4    | `i32` Unix timestamp of when the slot was saved                  |
+//!
+//! All integers are little-endian.
+
+use std::io;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use enum_iterator::all;
+use thiserror::Error;
+
+use super::{Preset, PresetEntry, PresetLibrary, Waymark};
+
+/// The number of preset slots in the file.
+pub const SLOTS: usize = 30;
+/// The length of the file header, in bytes.
+pub const HEADER_LEN: usize = 16;
+/// The length of a single slot, in bytes.
+pub const SLOT_LEN: usize = 104;
+/// The key that the slot data is XORed with.
+pub const XOR_KEY: u8 = 0x31;
+
+/// The number of waymarks in a slot.
+const WAYMARKS: usize = 8;
+/// Offset of the active waymark bitmask within a slot.
+const ACTIVE_OFFSET: usize = WAYMARKS * 12;
+/// Offset of the unknown byte within a slot.
+const UNKNOWN_OFFSET: usize = ACTIVE_OFFSET + 1;
+/// Offset of the map ID within a slot.
+const MAP_ID_OFFSET: usize = UNKNOWN_OFFSET + 1;
+/// Offset of the timestamp within a slot.
+const TIME_OFFSET: usize = MAP_ID_OFFSET + 2;
+/// The factor between the fixed-point coordinates in the file and yalms.
+const FIXED_POINT_SCALE: f32 = 1000.0;
+
+#[derive(Error, Debug)]
+pub enum FMarkerError {
+    #[error("Could not access waymark file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Waymark file is too short: expected at least {expected} bytes, found {found}")]
+    TooShort { expected: usize, found: usize },
+    #[error("There is no waymark slot {0}; slots are numbered from 1 to {SLOTS}")]
+    NoSuchSlot(usize),
+    #[error("Map ID {0} is too large to be stored in the waymark file")]
+    MapIdOutOfRange(u32),
+    #[error("Waymark coordinate {0} is too large to be stored in the waymark file")]
+    CoordOutOfRange(f32),
+}
+
+/// A single waymark position in a slot, in thousandths of a yalm.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SlotPoint {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/// A single saved waymark slot, exactly as it is stored in the file.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Slot {
+    /// The waymark positions, indexed by waymark ID.
+    pub points: [SlotPoint; WAYMARKS],
+    /// Bitmask of active waymarks, indexed by waymark ID.
+    pub active: u8,
+    unknown: u8,
+    pub map_id: u16,
+    /// Unix timestamp of when the slot was saved.
+    pub timestamp: i32,
+}
+
+impl Slot {
+    /// Produces true if nothing has been saved in this slot.
+    pub fn is_empty(&self) -> bool { self.map_id == 0 && self.active == 0 }
+
+    fn from_bytes(bytes: &[u8; SLOT_LEN]) -> Self {
+        let i32_at =
+            |offset: usize| i32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        let mut points = [SlotPoint::default(); WAYMARKS];
+        for (i, point) in points.iter_mut().enumerate() {
+            *point = SlotPoint {
+                x: i32_at(i * 12),
+                y: i32_at(i * 12 + 4),
+                z: i32_at(i * 12 + 8),
+            };
+        }
+        Self {
+            points,
+            active: bytes[ACTIVE_OFFSET],
+            unknown: bytes[UNKNOWN_OFFSET],
+            map_id: u16::from_le_bytes([bytes[MAP_ID_OFFSET], bytes[MAP_ID_OFFSET + 1]]),
+            timestamp: i32_at(TIME_OFFSET),
+        }
+    }
+
+    fn to_bytes(self) -> [u8; SLOT_LEN] {
+        let mut bytes = [0; SLOT_LEN];
+        for (i, point) in self.points.iter().enumerate() {
+            bytes[i * 12..i * 12 + 4].copy_from_slice(&point.x.to_le_bytes());
+            bytes[i * 12 + 4..i * 12 + 8].copy_from_slice(&point.y.to_le_bytes());
+            bytes[i * 12 + 8..i * 12 + 12].copy_from_slice(&point.z.to_le_bytes());
+        }
+        bytes[ACTIVE_OFFSET] = self.active;
+        bytes[UNKNOWN_OFFSET] = self.unknown;
+        bytes[MAP_ID_OFFSET..MAP_ID_OFFSET + 2].copy_from_slice(&self.map_id.to_le_bytes());
+        bytes[TIME_OFFSET..TIME_OFFSET + 4].copy_from_slice(&self.timestamp.to_le_bytes());
+        bytes
+    }
+
+    /// Converts this slot into a [`Preset`] with the given name.
+    pub fn to_preset(&self, name: String) -> Preset {
+        let waymarks = all::<Waymark>()
+            .map(|waymark| {
+                let id = u8::from(waymark);
+                let point = self.points[id as usize];
+                let entry = PresetEntry {
+                    x: point.x as f32 / FIXED_POINT_SCALE,
+                    y: point.y as f32 / FIXED_POINT_SCALE,
+                    z: point.z as f32 / FIXED_POINT_SCALE,
+                    id,
+                    active: self.active & (1 << id) != 0,
+                };
+                (waymark, entry)
+            })
+            .collect();
+        let time = DateTime::from_timestamp(i64::from(self.timestamp), 0)
+            .filter(|_| self.timestamp != 0)
+            .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, false));
+        Preset {
+            name,
+            map_id: u32::from(self.map_id),
+            time,
+            waymarks,
+        }
+    }
+
+    /// Converts a [`Preset`] into a slot.
+    ///
+    /// Waymarks missing from the preset are stored as inactive at the origin.
+    /// The slot is left without a timestamp.
+    pub fn from_preset(preset: &Preset) -> Result<Self, FMarkerError> {
+        let fixed = |coord: f32| {
+            let fixed = (coord * FIXED_POINT_SCALE).round();
+            if (i32::MIN as f32..=i32::MAX as f32).contains(&fixed) {
+                Ok(fixed as i32)
+            } else {
+                Err(FMarkerError::CoordOutOfRange(coord))
+            }
+        };
+
+        let mut slot = Slot {
+            map_id: preset
+                .map_id
+                .try_into()
+                .map_err(|_| FMarkerError::MapIdOutOfRange(preset.map_id))?,
+            ..Default::default()
+        };
+        for (&waymark, entry) in &preset.waymarks {
+            let id = u8::from(waymark);
+            slot.points[id as usize] = SlotPoint {
+                x: fixed(entry.x)?,
+                y: fixed(entry.y)?,
+                z: fixed(entry.z)?,
+            };
+            if entry.active {
+                slot.active |= 1 << id;
+            }
+        }
+        Ok(slot)
+    }
+}
+
+/// The contents of an `FMARKER.DAT` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FMarkerFile {
+    header: [u8; HEADER_LEN],
+    slots: [Slot; SLOTS],
+    trailer: Vec<u8>,
+}
+
+impl FMarkerFile {
+    /// Parses the contents of an `FMARKER.DAT` file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FMarkerError> {
+        let expected = HEADER_LEN + SLOTS * SLOT_LEN;
+        if bytes.len() < expected {
+            return Err(FMarkerError::TooShort {
+                expected,
+                found: bytes.len(),
+            });
+        }
+
+        let header = bytes[..HEADER_LEN].try_into().unwrap();
+        let mut slots = [Slot::default(); SLOTS];
+        for (i, slot) in slots.iter_mut().enumerate() {
+            let start = HEADER_LEN + i * SLOT_LEN;
+            let mut data: [u8; SLOT_LEN] = bytes[start..start + SLOT_LEN].try_into().unwrap();
+            data.iter_mut().for_each(|b| *b ^= XOR_KEY);
+            *slot = Slot::from_bytes(&data);
+        }
+        Ok(Self {
+            header,
+            slots,
+            trailer: bytes[expected..].to_vec(),
+        })
+    }
+
+    /// Serializes the file back into bytes.
+    ///
+    /// Anything we don't understand is written back exactly as it was read.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + SLOTS * SLOT_LEN + self.trailer.len());
+        bytes.extend_from_slice(&self.header);
+        for slot in &self.slots {
+            bytes.extend(slot.to_bytes().iter().map(|b| b ^ XOR_KEY));
+        }
+        bytes.extend_from_slice(&self.trailer);
+        bytes
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, FMarkerError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), FMarkerError> {
+        Ok(std::fs::write(path, self.to_bytes())?)
+    }
+
+    /// The slots in the file. Note that slot `n` in the game is at index `n - 1`.
+    pub fn slots(&self) -> &[Slot; SLOTS] { &self.slots }
+
+    /// Produces the preset saved in slot `n`, numbered from 1 as in the game, if there is one.
+    pub fn preset(&self, n: usize) -> Option<Preset> {
+        let slot = self.slots.get(n.checked_sub(1)?)?;
+        (!slot.is_empty()).then(|| slot.to_preset(Self::slot_name(n)))
+    }
+
+    /// Saves `preset` into slot `n`, numbered from 1 as in the game.
+    ///
+    /// The slot is stamped with the current time, as the game does, rather than the preset's.
+    pub fn set_preset(&mut self, n: usize, preset: &Preset) -> Result<(), FMarkerError> {
+        let slot = n
+            .checked_sub(1)
+            .and_then(|i| self.slots.get_mut(i))
+            .ok_or(FMarkerError::NoSuchSlot(n))?;
+        let unknown = slot.unknown;
+        *slot = Slot::from_preset(preset)?;
+        slot.unknown = unknown;
+        slot.timestamp = Utc::now().timestamp().try_into().unwrap_or_default();
+        Ok(())
+    }
+
+    /// Produces a [`PresetLibrary`] with the presets of all the non-empty slots.
+    pub fn to_library(&self) -> PresetLibrary {
+        PresetLibrary {
+            presets: (1..=SLOTS).filter_map(|n| self.preset(n)).collect(),
+        }
+    }
+
+    /// The name given to the preset in slot `n`.
+    pub fn slot_name(n: usize) -> String { format!("Slot {n}") }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Builds a file with a recognizable header and trailer and one saved slot.
+    fn sample_file() -> Vec<u8> {
+        let mut slot = Slot {
+            map_id: 694,
+            active: 0b0000_0101,
+            unknown: 0x42,
+            timestamp: 1_700_000_000,
+            ..Default::default()
+        };
+        slot.points[0] = SlotPoint {
+            x: 100_000,
+            y: -250,
+            z: 88_500,
+        };
+        slot.points[2] = SlotPoint {
+            x: 100_000,
+            y: 0,
+            z: 116_000,
+        };
+
+        let mut bytes = (0..HEADER_LEN as u8).collect::<Vec<_>>();
+        for i in 0..SLOTS {
+            let slot = if i == 2 { slot } else { Slot::default() };
+            bytes.extend(slot.to_bytes().iter().map(|b| b ^ XOR_KEY));
+        }
+        bytes.extend_from_slice(&[0xde, 0xad]);
+        bytes
+    }
+
+    #[test]
+    fn round_trip_is_lossless() {
+        let bytes = sample_file();
+        let file = FMarkerFile::from_bytes(&bytes).unwrap();
+        assert_eq!(file.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn slot_to_preset() {
+        let file = FMarkerFile::from_bytes(&sample_file()).unwrap();
+        assert_eq!(file.preset(1), None);
+        assert_eq!(file.to_library().len(), 1);
+
+        let preset = file.preset(3).unwrap();
+        assert_eq!(preset.name(), "Slot 3");
+        assert_eq!(preset.map_id(), 694);
+        assert_eq!(preset.time.as_deref(), Some("2023-11-14T22:13:20+00:00"));
+
+        let a = &preset.waymarks[&Waymark::A];
+        assert_eq!((a.x, a.y, a.z, a.active), (100.0, -0.25, 88.5, true));
+        assert!(!preset.waymarks[&Waymark::B].active);
+        assert!(preset.waymarks[&Waymark::C].active);
+    }
+
+    #[test]
+    fn preset_to_slot() {
+        let mut file = FMarkerFile::from_bytes(&sample_file()).unwrap();
+        let preset = file.preset(3).unwrap();
+        file.set_preset(5, &preset).unwrap();
+
+        let (three, five) = (file.slots()[2], file.slots()[4]);
+        assert_eq!(five.points, three.points);
+        assert_eq!(five.active, three.active);
+        assert_eq!(five.map_id, three.map_id);
+
+        assert!(matches!(
+            file.set_preset(31, &preset),
+            Err(FMarkerError::NoSuchSlot(31))
+        ));
+        assert!(matches!(
+            FMarkerFile::from_bytes(&[0; HEADER_LEN]),
+            Err(FMarkerError::TooShort { .. })
+        ));
+    }
+
+    #[test]
+    fn set_preset_updates_timestamp() {
+        let mut file = FMarkerFile::from_bytes(&sample_file()).unwrap();
+        let preset = file.preset(3).unwrap();
+        let before = Utc::now().timestamp();
+        file.set_preset(3, &preset).unwrap();
+
+        let timestamp = i64::from(file.slots()[2].timestamp);
+        assert!(timestamp >= before, "{timestamp} < {before}");
+        assert_ne!(file.preset(3).unwrap().time, preset.time);
+    }
+}
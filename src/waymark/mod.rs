@@ -3,14 +3,15 @@
 //! This module implements support for FFXIV waymarks.
 //! Waymarks can be manually manipulated, as well as imported and exported using the format of the Waymark Preset plugin.
 
+use std::{collections::BTreeMap, fmt};
+
 use avian2d::prelude::*;
 #[cfg(feature = "egui")]
 use bevy::window::RequestRedraw;
 use bevy::{
     color::palettes::css::{FUCHSIA, LIGHT_CYAN, RED, YELLOW},
-    ecs::{component::ComponentId, world::DeferredWorld},
+    ecs::{component::ComponentId, system::SystemParam, world::DeferredWorld},
     prelude::*,
-    utils::HashMap,
 };
 #[cfg(feature = "egui")]
 use bevy_vector_shapes::prelude::*;
@@ -20,13 +21,15 @@ use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    arena::{Arena, GameCoordOffset},
+    arena::{Arena, ArenaMeta, Arenas, GameCoordOffset},
     color::AlphaScale,
     drag::Draggable,
     image::{DrawImage, DrawImageKind},
     shape::{ColliderFromShape, DrawShape, Shape, Stroke},
+    view::Upright,
 };
 
+pub mod fmarker;
 #[cfg(feature = "egui")]
 mod window_egui;
 pub mod window {
@@ -57,18 +60,166 @@ pub struct Preset {
     map_id: u32,
 
     #[serde(flatten)]
-    waymarks: HashMap<Waymark, PresetEntry>,
+    waymarks: BTreeMap<Waymark, PresetEntry>,
+
+    /// The time the preset was last modified, as written by the plugin.
+    ///
+    /// We never interpret this, but keep it so that presets round-trip.
+    /// The plugin writes it after the waymarks, so it must stay after them here.
+    #[serde(rename = "Time", default, skip_serializing_if = "Option::is_none")]
+    time: Option<String>,
+}
+
+impl Preset {
+    /// The name of the preset.
+    pub fn name(&self) -> &str { &self.name }
+
+    /// The FFXIV map ID that the preset is for.
+    pub fn map_id(&self) -> u32 { self.map_id }
+
+    /// The time the preset was last modified, as written by the plugin.
+    pub fn time(&self) -> Option<&str> { self.time.as_deref() }
+
+    /// Checks the preset against `arena`, producing every issue found.
+    ///
+    /// Inactive waymarks are not checked.
+    pub fn validate(&self, arena: &ArenaMeta) -> Vec<PresetIssue> {
+        let mut issues = vec![];
+        if self.map_id != arena.map_id {
+            issues.push(PresetIssue::MapMismatch {
+                preset: self.map_id,
+                arena: arena.map_id,
+            });
+        }
+
+        let offset = GameCoordOffset(arena.offset);
+        let placed = self
+            .waymarks
+            .iter()
+            .filter(|(_, entry)| entry.active)
+            .map(|(&waymark, entry)| (waymark, entry.world_pos(offset)))
+            .collect_vec();
+        for &(waymark, pos) in &placed {
+            if !arena.shape.contains(pos) {
+                issues.push(PresetIssue::OutOfBounds(waymark));
+            }
+        }
+        for ((a, a_pos), (b, b_pos)) in placed.iter().copied().tuple_combinations() {
+            if a_pos.distance(b_pos) < WAYMARK_SIZE {
+                issues.push(PresetIssue::Overlapping(a, b));
+            }
+        }
+        issues
+    }
+
+    /// Creates a preset from its parts.
+    pub fn new(
+        name: String,
+        map_id: u32,
+        time: Option<String>,
+        waymarks: BTreeMap<Waymark, PresetEntry>,
+    ) -> Self {
+        Self {
+            name,
+            map_id,
+            time,
+            waymarks,
+        }
+    }
+}
+
+/// A problem with a [`Preset`], as found by [`Preset::validate`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PresetIssue {
+    /// The preset is for a different map than the arena.
+    MapMismatch { preset: u32, arena: u32 },
+    /// The waymark's center is outside the arena.
+    OutOfBounds(Waymark),
+    /// The two waymarks are close enough to overlap.
+    Overlapping(Waymark, Waymark),
+}
+
+impl fmt::Display for PresetIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PresetIssue::MapMismatch { preset, arena } => write!(
+                f,
+                "The preset is for map {preset}, but the arena is map {arena}."
+            ),
+            PresetIssue::OutOfBounds(waymark) => {
+                write!(f, "{} is outside the arena.", waymark.name())
+            }
+            PresetIssue::Overlapping(a, b) => {
+                write!(f, "{} overlaps {}.", a.name(), b.name())
+            }
+        }
+    }
+}
+
+/// A collection of waymark presets, as exported by the Waymark Preset plugin.
+///
+/// The plugin exports its library as a JSON array of presets;
+/// its configuration file stores them in an object under the key `Presets`.
+/// Both of these are accepted, as is a single preset on its own.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PresetLibrary {
+    presets: Vec<Preset>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PresetLibraryRepr {
+    List(Vec<Preset>),
+    Library {
+        #[serde(rename = "Presets")]
+        presets: Vec<Preset>,
+    },
+    Single(Preset),
+}
+
+impl PresetLibrary {
+    /// Parses a library from the JSON exported by the Waymark Preset plugin.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let presets = match serde_json::from_str(json)? {
+            PresetLibraryRepr::List(presets) | PresetLibraryRepr::Library { presets } => presets,
+            PresetLibraryRepr::Single(preset) => vec![preset],
+        };
+        Ok(Self { presets })
+    }
+
+    /// All the presets in the library, in the order they were exported.
+    pub fn presets(&self) -> &[Preset] { &self.presets }
+
+    pub fn len(&self) -> usize { self.presets.len() }
+
+    pub fn is_empty(&self) -> bool { self.presets.is_empty() }
+
+    /// Produces the presets in the library grouped by map ID, in ascending order of map ID.
+    ///
+    /// Within each map, presets retain their order from the library.
+    pub fn by_map(&self) -> BTreeMap<u32, Vec<&Preset>> {
+        let mut maps = BTreeMap::<u32, Vec<&Preset>>::new();
+        for preset in &self.presets {
+            maps.entry(preset.map_id).or_default().push(preset);
+        }
+        maps
+    }
 }
 
 /// A single waymark entry in the Waymark Preset format.
 ///
 /// Coordinates are all in the FFXIV coordinate system, not the Stratmap coordinate system.
+///
+/// As a component on a [`Waymark`], this is the entry that the waymark was spawned from.
+/// It is kept so that the waymark can be exported again without any change,
+/// and so that the height of the waymark is not lost even if it is moved.
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Component, Reflect)]
 pub struct PresetEntry {
     /// Corresponds to the X axis in Stratmap.
     #[serde(rename = "X")]
     x: f32,
-    /// Would be the Z axis in Stratmap and therefore always ignored by us.
+    /// Would be the Z axis in Stratmap.
+    /// We don't display this, but we keep it so that the height of the waymark is preserved.
     #[serde(rename = "Y")]
     y: f32,
     /// Corresponds to the negative Y axis in Stratmap.
@@ -77,11 +228,32 @@ pub struct PresetEntry {
     /// Numeric ID of the waymark (redundant but important for the plugin).
     #[serde(rename = "ID")]
     id: u8,
-    /// Whether the waymark is active. Stratmat simply discards inactive waymarks.
+    /// Whether the waymark is active.
+    /// Inactive waymarks are not shown, but are spawned as [`InactiveWaymark`]s.
     #[serde(rename = "Active")]
     active: bool,
 }
 
+impl PresetEntry {
+    /// Produces the waymark this entry is for, if its ID is valid.
+    pub fn waymark(&self) -> Option<Waymark> { Waymark::try_from(self.id).ok() }
+
+    /// Whether the waymark is placed.
+    pub fn is_active(&self) -> bool { self.active }
+
+    /// The position of this entry relative to the center of the arena with the given offset.
+    pub fn world_pos(&self, offset: GameCoordOffset) -> Vec2 {
+        offset.game_to_world(Vec2::new(self.x, self.z))
+    }
+}
+
+/// A waymark that was in an imported preset, but was not active.
+///
+/// It has no appearance and can't be interacted with;
+/// it's kept only so that it can be exported again.
+#[derive(Deref, Clone, Debug, PartialEq, Component, Reflect)]
+pub struct InactiveWaymark(pub PresetEntry);
+
 /// A placeable marker for players to reference movements during a fight.
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, Hash, PartialOrd, Ord, PartialEq, Eq)]
@@ -155,27 +327,94 @@ impl Waymark {
 
     /// Produces a [`PresetEntry`] corresponding to this waymark,
     /// using the provided [`Arena`] center `offset` and the provided [`Transform`].
-    pub fn to_entry(self, transform: &Transform, offset: Vec2) -> PresetEntry {
+    ///
+    /// If `original` is the entry that the waymark was spawned from, its height is kept,
+    /// and if the waymark is still where that entry put it, the entry is reproduced exactly.
+    pub fn to_entry(
+        self,
+        transform: &Transform,
+        offset: Vec2,
+        original: Option<&PresetEntry>,
+    ) -> PresetEntry {
+        let pos = transform.translation.truncate();
+        if let Some(original) = original {
+            // Converting back from world coordinates may not give the same float we started with.
+            if original.world_pos(GameCoordOffset(offset)) == pos {
+                return PresetEntry {
+                    id: u8::from(self),
+                    active: true,
+                    ..original.clone()
+                };
+            }
+        }
+        let game = GameCoordOffset(offset).world_to_game(pos);
         PresetEntry {
-            x: offset.x + transform.translation.x,
-            y: 0.0,
-            // The entry's Z axis is our negative Y axis.
-            z: offset.y - transform.translation.y,
+            x: game.x,
+            y: original.map_or(0.0, |original| original.y),
+            z: game.y,
             id: u8::from(self),
             active: true,
         }
     }
 
+    /// Collects the entries for a set of waymarks, including inactive ones, in waymark order.
+    ///
+    /// If a waymark is both active and inactive, the active one wins.
+    pub fn collect_entries<'a>(
+        active: impl IntoIterator<Item = (&'a Waymark, &'a Transform, Option<&'a PresetEntry>)>,
+        inactive: impl IntoIterator<Item = &'a InactiveWaymark>,
+        offset: Vec2,
+    ) -> BTreeMap<Waymark, PresetEntry> {
+        let mut entries = inactive
+            .into_iter()
+            .filter_map(|inactive| Some((inactive.waymark()?, inactive.0.clone())))
+            .collect::<BTreeMap<_, _>>();
+        for (&waymark, transform, original) in active {
+            entries.insert(waymark, waymark.to_entry(transform, offset, original));
+        }
+        entries
+    }
+
     pub fn spawn_from_preset(commands: &mut Commands, preset: Preset, parent: Entity) {
         for (waymark, entry) in preset.waymarks {
-            if entry.active {
-                commands.spawn((waymark, entry)).set_parent(parent);
-            }
+            Self::spawn_entry(commands, waymark, entry, parent);
+        }
+    }
+
+    /// Spawns waymarks from a list of [`PresetEntry`]s, using each entry's ID to identify the waymark.
+    pub fn spawn_from_entries(
+        commands: &mut Commands,
+        entries: impl IntoIterator<Item = PresetEntry>,
+        parent: Entity,
+    ) {
+        for entry in entries {
+            let Some(waymark) = entry.waymark() else {
+                warn!("Skipping waymark entry with invalid ID {}", entry.id);
+                continue;
+            };
+            Self::spawn_entry(commands, waymark, entry, parent);
+        }
+    }
+
+    /// Spawns a single waymark from a [`PresetEntry`], or an [`InactiveWaymark`] if it is inactive.
+    fn spawn_entry(commands: &mut Commands, waymark: Waymark, entry: PresetEntry, parent: Entity) {
+        if entry.active {
+            // The waymark goes in last, so that its arena is known when it is placed.
+            commands.spawn(entry).set_parent(parent).insert(waymark);
+        } else {
+            commands
+                .spawn((
+                    Name::new(format!("{} (Inactive)", waymark.name())),
+                    InactiveWaymark(entry),
+                ))
+                .set_parent(parent);
         }
     }
 
+    /// Despawns all waymarks, including inactive ones.
     pub fn despawn_all(world: &mut World) {
-        let mut query = world.query_filtered::<Entity, With<Waymark>>();
+        let mut query =
+            world.query_filtered::<Entity, Or<(With<Waymark>, With<InactiveWaymark>)>>();
         let entities = query.iter(world).collect_vec();
         for entity in entities {
             world.entity_mut(entity).despawn_recursive();
@@ -191,7 +430,7 @@ impl Waymark {
             In(id): In<Entity>,
             q: Query<(&Waymark, Option<&PresetEntry>)>,
             asset_server: Res<AssetServer>,
-            offset: Option<Res<GameCoordOffset>>,
+            arenas: Arenas,
             mut commands: Commands,
         ) {
             let Ok((&waymark, preset_entry)) = q.get(id) else {
@@ -206,17 +445,12 @@ impl Waymark {
             let mut entity = commands.entity(id);
 
             if let Some(entry) = preset_entry {
-                if let Some(offset) = offset {
-                    let (x, y) = (entry.x - offset.x, offset.y - entry.z);
-                    debug!("world coords: {:?}", (x, y),);
-                    entity.insert(Transform::from_xyz(
-                        entry.x - offset.x,
-                        // The entry's Z axis is our negative Y axis.
-                        offset.y - entry.z,
-                        WAYMARK_Z,
-                    ));
+                if let Some((_, arena)) = arenas.arena_of(id) {
+                    let pos = entry.world_pos(arena.offset());
+                    debug!("arena coords: {:?}", pos);
+                    entity.insert(Transform::from_translation(pos.extend(WAYMARK_Z)));
                 } else {
-                    error!("Unable to spawn waymark because GameCoordOffset is not available.");
+                    error!("Unable to spawn waymark because it is not on an arena.");
                     return;
                 }
             } else {
@@ -229,8 +463,12 @@ impl Waymark {
                 Shape::Rectangle(Rectangle::from_length(WAYMARK_SIZE))
             };
 
-            entity.insert((Name::new(waymark.name()), waymark, shape, ColliderFromShape));
-            entity.remove::<PresetEntry>();
+            entity.insert((
+                Name::new(waymark.name()),
+                waymark,
+                shape.clone(),
+                ColliderFromShape,
+            ));
 
             entity.with_children(|parent| {
                 #[cfg_attr(not(feature = "egui"), allow(unused_variables))]
@@ -241,6 +479,7 @@ impl Waymark {
                         Vec2::splat(WAYMARK_SIZE * IMAGE_SCALE),
                         DrawImageKind::Sprite,
                     ),
+                    Upright,
                     AlphaScale::default(),
                 ));
 
@@ -258,6 +497,56 @@ impl Waymark {
     }
 }
 
+/// [`SystemParam`] for the waymarks on the board, and the arena they are on.
+#[derive(SystemParam)]
+pub struct Waymarks<'w, 's> {
+    active_q: Query<'w, 's, (Entity, &'static Waymark, Option<&'static PresetEntry>)>,
+    inactive_q: Query<'w, 's, (Entity, &'static InactiveWaymark)>,
+    arenas: Arenas<'w, 's>,
+}
+
+impl Waymarks<'_, '_> {
+    /// Produces the arena the waymarks are on.
+    ///
+    /// As in game, the waymarks are treated as a single set, so this is the arena with the most
+    /// waymarks on it. Ties go to the leftmost arena, so with no waymarks it is the primary arena.
+    pub fn arena(&self) -> Option<(Entity, &Arena)> {
+        let ids = self
+            .active_q
+            .iter()
+            .map(|(id, ..)| id)
+            .chain(self.inactive_q.iter().map(|(id, _)| id))
+            .filter_map(|id| self.arenas.arena_of(id).map(|(arena, _)| arena))
+            .counts();
+        self.arenas
+            .all()
+            .into_iter()
+            .rev()
+            .max_by_key(|(id, _)| ids.get(id).copied().unwrap_or(0))
+    }
+
+    /// Produces the entries for every waymark, including inactive ones,
+    /// in the game coordinates of [`Self::arena`].
+    pub fn entries(&self) -> Option<(&Arena, BTreeMap<Waymark, PresetEntry>)> {
+        let (arena_id, arena) = self.arena()?;
+        let active = self
+            .active_q
+            .iter()
+            .filter_map(|(id, waymark, original)| {
+                Some((waymark, self.arenas.transform_on(id, arena_id)?, original))
+            })
+            .collect_vec();
+        let entries = Waymark::collect_entries(
+            active
+                .iter()
+                .map(|(waymark, transform, original)| (*waymark, transform, *original)),
+            self.inactive_q.iter().map(|(_, inactive)| inactive),
+            arena.offset,
+        );
+        Some((arena, entries))
+    }
+}
+
 /// Plugin for waymark support.
 #[derive(Default, Copy, Clone, Debug)]
 pub struct WaymarkPlugin;
@@ -267,3 +556,166 @@ impl Plugin for WaymarkPlugin {
 }
 
 pub fn plugin() -> WaymarkPlugin { WaymarkPlugin }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LIBRARY: &str = r#"[
+  {"Name":"TEA","MapID":694,"Time":"2023-05-01T12:00:00.0000000+00:00",
+   "A":{"X":100.0,"Y":0.0,"Z":88.0,"ID":0,"Active":true},
+   "B":{"X":0.0,"Y":0.0,"Z":0.0,"ID":1,"Active":false},
+   "C":{"X":0.0,"Y":0.0,"Z":0.0,"ID":2,"Active":false},
+   "D":{"X":0.0,"Y":0.0,"Z":0.0,"ID":3,"Active":false},
+   "One":{"X":0.0,"Y":0.0,"Z":0.0,"ID":4,"Active":false},
+   "Two":{"X":0.0,"Y":0.0,"Z":0.0,"ID":5,"Active":false},
+   "Three":{"X":0.0,"Y":0.0,"Z":0.0,"ID":6,"Active":false},
+   "Four":{"X":0.0,"Y":0.0,"Z":0.0,"ID":7,"Active":false}},
+  {"Name":"FRU","MapID":1238,
+   "A":{"X":100.0,"Y":0.0,"Z":90.0,"ID":0,"Active":true}},
+  {"Name":"TEA (alt)","MapID":694,
+   "B":{"X":110.0,"Y":0.0,"Z":100.0,"ID":1,"Active":true}}
+]"#;
+
+    #[test]
+    fn library_grouped_by_map() {
+        let library = PresetLibrary::from_json(LIBRARY).unwrap();
+        assert_eq!(library.len(), 3);
+
+        let maps = library.by_map();
+        assert_eq!(maps.keys().copied().collect_vec(), vec![694, 1238]);
+        let tea = maps[&694].iter().map(|p| p.name()).collect_vec();
+        assert_eq!(tea, vec!["TEA", "TEA (alt)"]);
+    }
+
+    /// Simulates spawning a preset and then exporting it again, possibly after moving one waymark.
+    fn respawn(preset: &Preset, offset: Vec2, moved: Option<(Waymark, Vec2)>) -> Preset {
+        let mut active = vec![];
+        let mut inactive = vec![];
+        for (&waymark, entry) in &preset.waymarks {
+            if !entry.is_active() {
+                inactive.push(InactiveWaymark(entry.clone()));
+                continue;
+            }
+            let pos = match moved {
+                Some((moved, pos)) if moved == waymark => pos,
+                _ => entry.world_pos(GameCoordOffset(offset)),
+            };
+            let transform = Transform::from_translation(pos.extend(WAYMARK_Z));
+            active.push((waymark, transform, entry.clone()));
+        }
+        let active = active
+            .iter()
+            .map(|(waymark, transform, entry)| (waymark, transform, Some(entry)));
+        Preset::new(
+            preset.name.clone(),
+            preset.map_id,
+            preset.time.clone(),
+            Waymark::collect_entries(active, &inactive, offset),
+        )
+    }
+
+    const ELEVATED: &str = concat!(
+        r#"{"Name":"Elevated","MapID":1238,"#,
+        r#""A":{"X":92.2,"Y":-0.015,"Z":107.8,"ID":0,"Active":true},"#,
+        r#""B":{"X":0.0,"Y":0.0,"Z":0.0,"ID":1,"Active":false},"#,
+        r#""C":{"X":100.0,"Y":-0.015,"Z":116.13,"ID":2,"Active":true},"#,
+        r#""D":{"X":83.333,"Y":12.5,"Z":100.0,"ID":3,"Active":true},"#,
+        r#""One":{"X":-7.1,"Y":4.2,"Z":3.3,"ID":4,"Active":false},"#,
+        r#""Two":{"X":0.0,"Y":0.0,"Z":0.0,"ID":5,"Active":false},"#,
+        r#""Three":{"X":107.8,"Y":-0.015,"Z":107.8,"ID":6,"Active":true},"#,
+        r#""Four":{"X":0.0,"Y":0.0,"Z":0.0,"ID":7,"Active":false},"#,
+        r#""Time":"2024-12-03T21:45:10.1234567-05:00"}"#,
+    );
+
+    #[test]
+    fn round_trip_is_byte_for_byte() {
+        let preset: Preset = serde_json::from_str(ELEVATED).unwrap();
+        for offset in [
+            Vec2::new(100.0, 100.0),
+            Vec2::new(-12.34, 56.78),
+            Vec2::ZERO,
+        ] {
+            let exported = respawn(&preset, offset, None);
+            assert_eq!(exported, preset);
+            assert_eq!(serde_json::to_string(&exported).unwrap(), ELEVATED);
+        }
+    }
+
+    #[test]
+    fn moved_waymark_keeps_height() {
+        let preset: Preset = serde_json::from_str(ELEVATED).unwrap();
+        let offset = Vec2::new(100.0, 100.0);
+        let exported = respawn(&preset, offset, Some((Waymark::D, Vec2::new(-10.0, 5.0))));
+
+        let d = &exported.waymarks[&Waymark::D];
+        assert_eq!((d.x, d.y, d.z, d.active), (90.0, 12.5, 95.0, true));
+        for waymark in enum_iterator::all::<Waymark>().filter(|&w| w != Waymark::D) {
+            assert_eq!(exported.waymarks[&waymark], preset.waymarks[&waymark]);
+        }
+    }
+
+    #[test]
+    fn active_waymark_overrides_inactive() {
+        let preset: Preset = serde_json::from_str(ELEVATED).unwrap();
+        let inactive = vec![InactiveWaymark(preset.waymarks[&Waymark::B].clone())];
+        let transform = Transform::from_xyz(1.0, 2.0, WAYMARK_Z);
+        let entries = Waymark::collect_entries(
+            [(&Waymark::B, &transform, None)],
+            &inactive,
+            Vec2::new(100.0, 100.0),
+        );
+        let b = &entries[&Waymark::B];
+        assert_eq!((b.x, b.y, b.z, b.active), (101.0, 0.0, 98.0, true));
+    }
+
+    #[test]
+    fn validate_against_arena() {
+        let arena = ArenaMeta {
+            name: "Test Arena".into(),
+            short_name: "Test".into(),
+            map_id: 1238,
+            background_path: String::new(),
+            size: Vec2::splat(40.0),
+            offset: Vec2::new(100.0, 100.0),
+            shape: Shape::Circle(Circle::new(20.0)),
+            overlays: vec![],
+            asset_path: String::new(),
+        };
+
+        let preset: Preset = serde_json::from_str(ELEVATED).unwrap();
+        assert_eq!(preset.validate(&arena), vec![]);
+
+        let preset: Preset = serde_json::from_str(concat!(
+            r#"{"Name":"Bad","MapID":1006,"#,
+            r#""A":{"X":100.0,"Y":0.0,"Z":88.0,"ID":0,"Active":true},"#,
+            r#""B":{"X":100.5,"Y":0.0,"Z":88.5,"ID":1,"Active":true},"#,
+            r#""C":{"X":130.0,"Y":0.0,"Z":100.0,"ID":2,"Active":true},"#,
+            r#""D":{"X":100.0,"Y":0.0,"Z":88.0,"ID":3,"Active":false},"#,
+            r#""One":{"X":500.0,"Y":0.0,"Z":500.0,"ID":4,"Active":false}}"#,
+        ))
+        .unwrap();
+        assert_eq!(preset.validate(&arena), vec![
+            PresetIssue::MapMismatch {
+                preset: 1006,
+                arena: 1238
+            },
+            PresetIssue::OutOfBounds(Waymark::C),
+            PresetIssue::Overlapping(Waymark::A, Waymark::B),
+        ]);
+    }
+
+    #[test]
+    fn library_formats() {
+        let wrapped = format!(r#"{{"Presets": {LIBRARY}}}"#);
+        assert_eq!(
+            PresetLibrary::from_json(&wrapped).unwrap(),
+            PresetLibrary::from_json(LIBRARY).unwrap(),
+        );
+
+        let single =
+            r#"{"Name":"Solo","MapID":1,"A":{"X":1.0,"Y":0.0,"Z":2.0,"ID":0,"Active":true}}"#;
+        let library = PresetLibrary::from_json(single).unwrap();
+        assert_eq!(library.presets()[0].name(), "Solo");
+    }
+}
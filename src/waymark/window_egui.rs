@@ -1,5 +1,8 @@
 //! Waymark tray and associated code.
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+
 use bevy::{
     ecs::{component::ComponentId, system::SystemState, world::DeferredWorld},
     prelude::*,
@@ -7,25 +10,33 @@ use bevy::{
 use bevy_egui::{egui, egui::TextEdit, EguiClipboard};
 use itertools::Itertools;
 
-use super::{Preset, Waymark, WAYMARK_Z};
+#[cfg(not(target_arch = "wasm32"))]
+use super::fmarker::{self, FMarkerFile};
+use super::{Preset, PresetIssue, PresetLibrary, Waymark, Waymarks, WAYMARK_Z};
 use crate::{
-    arena::Arena,
+    arena::{arenas_for_map, replace_arena, Arena, ArenaListing, ArenaMeta},
+    asset::OptionalGlobalAsset,
     ecs::{EntityWorldExts, NestedSystemExts},
-    ui::widget::{egui_context, Widget, WidgetSystemId},
     spawner::{self, panel::SpawnerPanel, Spawnable, Spawner},
+    ui::widget::{egui_context, Widget, WidgetSystemId},
 };
 
 const SPAWNER_SIZE: f32 = 40.0;
 const SPAWNER_SEP: f32 = 5.0;
+/// The maximum height of the preset library list before it scrolls.
+const LIBRARY_HEIGHT: f32 = 240.0;
 
 impl Spawnable for Waymark {
     const UNIQUE: bool = true;
     const Z: f32 = WAYMARK_Z;
 
+    type SortKey = Self;
+
     fn size() -> Vec2 { Vec2::splat(SPAWNER_SIZE) }
     fn sep() -> Vec2 { Vec2::splat(SPAWNER_SEP) }
 
     fn spawner_name(&self) -> std::borrow::Cow<'static, str> { self.name().into() }
+    fn sort_key(&self) -> Self { *self }
 
     fn insert(&self, entity: &mut bevy::ecs::system::EntityCommands) { entity.insert(*self); }
 }
@@ -35,6 +46,29 @@ impl Spawnable for Waymark {
 #[component(on_add = Self::on_add)]
 pub struct WaymarkWindow {
     preset_name: String,
+    /// The timestamp of the last imported preset, kept so that it can be exported unchanged.
+    preset_time: Option<String>,
+    /// The last imported preset, kept so that it can be re-imported onto a different arena.
+    #[reflect(ignore)]
+    imported: Option<Preset>,
+    /// The issues found when the last preset was imported.
+    #[reflect(ignore)]
+    issues: Vec<PresetIssue>,
+    /// The most recently imported preset library, if any.
+    #[reflect(ignore)]
+    library: Option<PresetLibrary>,
+    /// Whether to show presets for all maps in the library, rather than just the current one.
+    show_all_maps: bool,
+    /// The path to load a library file from.
+    #[cfg(not(target_arch = "wasm32"))]
+    library_path: String,
+    /// The game's saved waymark file, if the library was loaded from one, along with its path.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[reflect(ignore)]
+    fmarker: Option<(PathBuf, FMarkerFile)>,
+    /// The slot to write the current waymarks to in the game's saved waymark file.
+    #[cfg(not(target_arch = "wasm32"))]
+    fmarker_slot: usize,
 }
 
 impl WaymarkWindow {
@@ -45,16 +79,28 @@ impl WaymarkWindow {
             Query<(Entity, &mut WaymarkWindow)>,
             Query<&Widget, With<SpawnerPanel<Waymark>>>,
             Query<&Children>,
-            Query<Entity, With<Arena>>,
+            Waymarks,
             Commands,
             ResMut<EguiClipboard>,
+            OptionalGlobalAsset<ArenaListing>,
+            Res<Assets<ArenaMeta>>,
+            Res<AssetServer>,
         )>::new(world);
 
         let ewin =
             egui::Window::new("Waymarks").default_width(4.0 * (Waymark::size() + Waymark::sep()).x);
         ewin.show(&ctx, |ui| {
-            let (mut win_q, panel_q, children_q, arena_q, mut commands, mut clipboard) =
-                state.get_mut(world);
+            let (
+                mut win_q,
+                panel_q,
+                children_q,
+                waymarks,
+                mut commands,
+                mut clipboard,
+                listing,
+                arena_assets,
+                asset_server,
+            ) = state.get_mut(world);
             let (win_id, mut win) = win_q.single_mut();
 
             ui.horizontal(|ui| {
@@ -62,17 +108,12 @@ impl WaymarkWindow {
                 ui.add(TextEdit::singleline(&mut win.preset_name).desired_width(80.0));
             });
             ui.horizontal(|ui| {
-                let arena = arena_q.get_single().ok();
+                let arena = waymarks.arena();
                 if ui
                     .add_enabled(arena.is_some(), egui::Button::new("Import"))
                     .clicked()
                 {
-                    Self::import_from_clipboard(
-                        &mut win.preset_name,
-                        &mut clipboard,
-                        &mut commands,
-                        arena,
-                    );
+                    Self::import_from_clipboard(&mut win, &mut clipboard, &mut commands, arena);
                 }
                 if ui.button("Export").clicked() {
                     commands.run_system_cached(Self::export_to_clipboard);
@@ -86,6 +127,54 @@ impl WaymarkWindow {
                 bevy_egui::egui::RichText::new("To paste, press Ctrl-C then click Import.")
                     .italics(),
             );
+            #[cfg(not(target_arch = "wasm32"))]
+            ui.horizontal(|ui| {
+                ui.label("Library File: ");
+                ui.add(TextEdit::singleline(&mut win.library_path).desired_width(80.0));
+                if ui
+                    .add_enabled(!win.library_path.is_empty(), egui::Button::new("Load"))
+                    .clicked()
+                {
+                    Self::load_library_file(&mut win);
+                }
+            });
+            #[cfg(not(target_arch = "wasm32"))]
+            if win.fmarker.is_some() {
+                ui.horizontal(|ui| {
+                    ui.label("Slot: ");
+                    ui.add(egui::DragValue::new(&mut win.fmarker_slot).range(1..=fmarker::SLOTS));
+                    if ui.button("Save to Slot").clicked() {
+                        commands.run_system_cached_with(Self::write_to_fmarker, win.fmarker_slot);
+                    }
+                });
+            }
+
+            if win.library.is_some() {
+                ui.separator();
+                let arena = waymarks.arena();
+                let current_map = arena.map(|(_, arena)| arena.map_id);
+                if let Some(preset) = Self::show_library(ui, &mut win, current_map) {
+                    if let Some(arena) = arena {
+                        Self::load_preset(&mut win, &mut commands, preset, arena);
+                    }
+                }
+            }
+
+            if !win.issues.is_empty() {
+                ui.separator();
+                let candidates = listing.option().as_ref().map_or_else(Vec::new, |listing| {
+                    win.imported.as_ref().map_or_else(Vec::new, |preset| {
+                        arenas_for_map(listing, &asset_server, &arena_assets, preset.map_id)
+                    })
+                });
+                if let Some(arena) = Self::show_issues(ui, &win.issues, &candidates) {
+                    if let Some(preset) = win.imported.clone() {
+                        win.issues = preset.validate(arena);
+                        commands
+                            .run_system_cached_with(Self::switch_arena, (arena.clone(), preset));
+                    }
+                }
+            }
             ui.separator();
 
             let panel = panel_q
@@ -99,11 +188,205 @@ impl WaymarkWindow {
         });
     }
 
+    /// Shows the preset library browser, producing the preset that was clicked on, if any.
+    ///
+    /// Presets are grouped by map, and unless the user asks to see them all,
+    /// only those for the map of the current arena are shown.
+    fn show_library(
+        ui: &mut egui::Ui,
+        win: &mut WaymarkWindow,
+        current_map: Option<u32>,
+    ) -> Option<Preset> {
+        let WaymarkWindow {
+            library: library_slot,
+            show_all_maps,
+            ..
+        } = win;
+        let library = library_slot.as_ref()?;
+
+        let mut chosen = None;
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.label(format!("Library ({} presets)", library.len()));
+            ui.checkbox(show_all_maps, "All Maps");
+            close = ui.button("Close").clicked();
+        });
+
+        egui::ScrollArea::vertical()
+            .max_height(LIBRARY_HEIGHT)
+            .show(ui, |ui| {
+                let mut shown = false;
+                for (map_id, presets) in library.by_map() {
+                    let is_current = current_map == Some(map_id);
+                    if !*show_all_maps && current_map.is_some() && !is_current {
+                        continue;
+                    }
+                    shown = true;
+
+                    let header = if is_current {
+                        format!("Map {map_id} (current arena)")
+                    } else {
+                        format!("Map {map_id}")
+                    };
+                    egui::CollapsingHeader::new(header)
+                        .default_open(is_current)
+                        .show(ui, |ui| {
+                            for preset in presets {
+                                let button = egui::Button::new(preset.name());
+                                if ui.add_enabled(current_map.is_some(), button).clicked() {
+                                    chosen = Some(preset.clone());
+                                }
+                            }
+                        });
+                }
+                if !shown {
+                    ui.label(egui::RichText::new("No presets for the current arena.").italics());
+                }
+            });
+
+        if close {
+            *library_slot = None;
+        }
+        chosen
+    }
+
+    /// Shows the issues found with the last imported preset.
+    ///
+    /// If the preset is for a different map, this offers to switch to any of the `candidates`,
+    /// producing the one that was chosen.
+    fn show_issues<'a>(
+        ui: &mut egui::Ui,
+        issues: &[PresetIssue],
+        candidates: &[&'a ArenaMeta],
+    ) -> Option<&'a ArenaMeta> {
+        let warn_color = ui.visuals().warn_fg_color;
+        for issue in issues {
+            ui.colored_label(warn_color, issue.to_string());
+        }
+
+        let mismatched = issues
+            .iter()
+            .any(|issue| matches!(issue, PresetIssue::MapMismatch { .. }));
+        if !mismatched || candidates.is_empty() {
+            return None;
+        }
+        let mut chosen = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Switch to: ");
+            for &arena in candidates {
+                if ui.button(&arena.short_name).clicked() {
+                    chosen = Some(arena);
+                }
+            }
+        });
+        chosen
+    }
+
+    /// Replaces the current waymarks with those from `preset`, and checks it against the arena.
+    fn load_preset(
+        win: &mut WaymarkWindow,
+        commands: &mut Commands,
+        preset: Preset,
+        (id, arena): (Entity, &Arena),
+    ) {
+        win.preset_name.clone_from(&preset.name);
+        win.preset_time.clone_from(&preset.time);
+        win.issues = preset.validate(arena);
+        for issue in &win.issues {
+            warn!("Waymark preset '{}': {}", win.preset_name, issue);
+        }
+        commands.run_system_cached(Waymark::despawn_all);
+        Waymark::spawn_from_preset(commands, preset.clone(), id);
+        win.imported = Some(preset);
+        info!("Loaded waymark preset '{}'", win.preset_name);
+    }
+
+    /// [System] that replaces the arena the waymarks are on with a different arena,
+    /// and then spawns the waymark preset onto it.
+    ///
+    /// Everything on the replaced arena is moved onto the new one. Any other arenas are left alone.
+    fn switch_arena(
+        In((arena, preset)): In<(ArenaMeta, Preset)>,
+        waymarks: Waymarks,
+        transform_q: Query<&Transform>,
+        mut commands: Commands,
+    ) {
+        info!(
+            "Switching to arena '{}' for waymark preset '{}'",
+            arena.name, preset.name
+        );
+        commands.run_system_cached(Waymark::despawn_all);
+        let old = waymarks.arena().map(|(old, _)| old);
+        let position = old
+            .and_then(|old| transform_q.get(old).ok())
+            .map_or(Vec2::ZERO, |transform| transform.translation.truncate());
+        let id = Arena::spawn(&mut commands, arena, position);
+        if let Some(old) = old {
+            commands.queue(move |world: &mut World| replace_arena(old, id, world));
+        }
+        Waymark::spawn_from_preset(&mut commands, preset, id);
+    }
+
+    /// Loads a preset library from [`Self::library_path`].
+    ///
+    /// This can either be a library exported by the Waymark Preset plugin,
+    /// or the game's own `FMARKER.DAT`.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_library_file(win: &mut WaymarkWindow) {
+        let path = Path::new(&win.library_path);
+        let is_dat = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("dat"));
+        if is_dat {
+            match FMarkerFile::read(path) {
+                Ok(file) => {
+                    let library = file.to_library();
+                    info!(
+                        "Loaded {} saved waymark presets from '{}'",
+                        library.len(),
+                        win.library_path
+                    );
+                    win.library = Some(library);
+                    win.fmarker = Some((path.to_owned(), file));
+                    win.fmarker_slot = win.fmarker_slot.clamp(1, fmarker::SLOTS);
+                }
+                Err(e) => warn!("Unable to load waymark file: {}", e),
+            }
+            return;
+        }
+
+        let contents = match std::fs::read_to_string(&win.library_path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                warn!(
+                    "Unable to read waymark library '{}': {}",
+                    win.library_path, e
+                );
+                return;
+            }
+        };
+        match PresetLibrary::from_json(&contents) {
+            Ok(library) => {
+                info!(
+                    "Loaded {} waymark presets from '{}'",
+                    library.len(),
+                    win.library_path
+                );
+                win.library = Some(library);
+                win.fmarker = None;
+            }
+            Err(e) => warn!("Unable to load waymark library: invalid library: {}", e),
+        }
+    }
+
+    /// Imports from the clipboard.
+    ///
+    /// A single preset is spawned immediately, while a whole library is opened for browsing.
     fn import_from_clipboard(
-        preset_name: &mut String,
+        win: &mut WaymarkWindow,
         clipboard: &mut EguiClipboard,
         commands: &mut Commands,
-        arena: Option<Entity>,
+        arena: Option<(Entity, &Arena)>,
     ) {
         let Some(arena) = arena else {
             error!("Unable to import waymarks: arena not loaded");
@@ -122,34 +405,43 @@ impl WaymarkWindow {
 
         match serde_json::from_str::<Preset>(&contents) {
             Ok(preset) => {
-                *preset_name = preset.name.clone();
-                commands.run_system_cached(Waymark::despawn_all);
-                Waymark::spawn_from_preset(commands, preset, arena);
-                info!(
-                    "Imported waymark preset '{}' from the clipboard",
-                    preset_name
-                );
-            }
-            Err(e) => {
-                info!("Unable to import waymarks: invalid preset: {}", e);
+                Self::load_preset(win, commands, preset, arena);
             }
+            Err(e) => match PresetLibrary::from_json(&contents) {
+                Ok(library) => {
+                    info!(
+                        "Imported a library of {} waymark presets from the clipboard",
+                        library.len()
+                    );
+                    win.library = Some(library);
+                }
+                Err(_) => warn!("Unable to import waymarks: invalid preset: {}", e),
+            },
         }
     }
 
+    /// Produces a preset from the currently-spawned waymarks, including inactive ones.
+    ///
+    /// Produces `None` if there is no arena.
+    fn current_preset(&self, waymarks: &Waymarks) -> Option<Preset> {
+        let (arena, entries) = waymarks.entries()?;
+        Some(Preset {
+            name: self.preset_name.clone(),
+            map_id: arena.map_id,
+            time: self.preset_time.clone(),
+            waymarks: entries,
+        })
+    }
+
     /// [System] that exports the currently-spawned waymarks to the clipboard.
     pub fn export_to_clipboard(
         win_q: Query<&WaymarkWindow>,
-        waymarks_q: Query<(&Waymark, &Transform)>,
-        arena: Single<&Arena>,
+        waymarks: Waymarks,
         mut clipboard: ResMut<EguiClipboard>,
     ) {
-        let preset = Preset {
-            name: win_q.single().preset_name.clone(),
-            map_id: arena.map_id,
-            waymarks: waymarks_q
-                .iter()
-                .map(|(&waymark, transform)| (waymark, waymark.to_entry(transform, arena.offset)))
-                .collect(),
+        let Some(preset) = win_q.single().current_preset(&waymarks) else {
+            error!("Unable to export waymarks: arena not loaded");
+            return;
         };
         match serde_json::to_string(&preset) {
             Ok(json) => {
@@ -160,6 +452,49 @@ impl WaymarkWindow {
         }
     }
 
+    /// [System] that saves the currently-spawned waymarks into a slot of the loaded `FMARKER.DAT`,
+    /// and writes it back to disk.
+    ///
+    /// The first write copies the original to `FMARKER.DAT.bak`, unless that backup already exists.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn write_to_fmarker(
+        In(slot): In<usize>,
+        mut win_q: Query<&mut WaymarkWindow>,
+        waymarks: Waymarks,
+    ) {
+        let mut win = win_q.single_mut();
+        let Some(preset) = win.current_preset(&waymarks) else {
+            error!("Unable to save waymarks to slot {slot}: arena not loaded");
+            return;
+        };
+        let win = &mut *win;
+        let Some((path, file)) = &mut win.fmarker else {
+            error!("Unable to save waymarks to slot {slot}: no waymark file loaded");
+            return;
+        };
+
+        // The game may have written to the file since it was loaded, so start from what is on disk.
+        let result = FMarkerFile::read(&*path).and_then(|mut current| {
+            let mut backup = path.clone().into_os_string();
+            backup.push(".bak");
+            if !Path::new(&backup).exists() {
+                std::fs::copy(&*path, &backup)?;
+            }
+            current.set_preset(slot, &preset)?;
+            current.write(&*path)?;
+            Ok(current)
+        });
+        match result {
+            Ok(current) => *file = current,
+            Err(e) => {
+                error!("Unable to save waymarks to slot {slot}: {e}");
+                return;
+            }
+        }
+        win.library = Some(file.to_library());
+        info!("Saved waymarks to slot {slot} of '{}'", path.display());
+    }
+
     /// Setup the window.
     pub fn on_add(mut world: DeferredWorld, id: Entity, _: ComponentId) {
         world.commands().queue(move |world: &mut World| {